@@ -5,8 +5,8 @@ use std::io;
 use std::io::Write;
 use std::net::Ipv4Addr;
 use warts::{
-    Address, CycleStart, CycleStop, Flags, List, Object, Timeval, TraceGapAction, TraceProbe,
-    TraceStopReason, TraceType, Traceroute,
+    Address, CycleStart, CycleStop, Flags, List, Object, Timeval, TraceGapAction, TraceHopFlags,
+    TraceLoopAction, TraceProbe, TraceStopReason, TraceType, Traceroute,
 };
 
 fn main() -> io::Result<()> {
@@ -49,7 +49,7 @@ fn main() -> io::Result<()> {
         addr_id: None,
         probe_ttl: Some(1),
         reply_ttl: Some(254),
-        hop_flags: Some(17),
+        hop_flags: Some(TraceHopFlags::TS_SOCK_RX | TraceHopFlags::REPLY_TTL),
         probe_id: Some(0),
         rtt_usec: Some(1057),
         icmp_type: Some(11),
@@ -66,7 +66,7 @@ fn main() -> io::Result<()> {
         icmp_extensions_length: None,
         icmp_extensions: vec![],
         addr: Some(Address::from(Ipv4Addr::new(137, 194, 164, 254))),
-        tx: Some(Timeval::from(Utc::now().naive_utc())),
+        tx: Timeval::try_from(Utc::now()).ok(),
     };
 
     let traceroute = Traceroute {
@@ -77,7 +77,7 @@ fn main() -> io::Result<()> {
         cycle_id: Some(1),
         src_addr_id: None,
         dst_addr_id: None,
-        start_time: Some(Timeval::from(Utc::now().naive_utc())),
+        start_time: Timeval::try_from(Utc::now()).ok(),
         stop_reason: Some(TraceStopReason::Completed),
         stop_data: Some(0),
         trace_flags: None,
@@ -94,7 +94,7 @@ fn main() -> io::Result<()> {
         hops_probed: Some(7),
         gap_limit: Some(5),
         gap_limit_action: Some(TraceGapAction::LastDitch),
-        loop_action: Some(0),
+        loop_action: Some(TraceLoopAction::Quit),
         probes_sent: Some(8),
         interval_csec: Some(0),
         confidence_level: Some(0),
@@ -105,6 +105,10 @@ fn main() -> io::Result<()> {
         router_addr: None,
         hop_count: 1,
         hops: vec![tp.finalize()],
+        pmtud: None,
+        last_ditch: None,
+        dtree: None,
+        other_attributes: vec![],
         eof: 0,
     };
     io::stdout().write_all(
@@ -13,7 +13,7 @@ use crate::WartsSized;
 /// // Warts to Rust:
 /// let ip = IpAddr::from(address);
 /// ```
-#[derive(Copy, Clone, Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
 pub enum Address {
     // NOTE: We use the length field as a type tag.
@@ -29,6 +29,17 @@ pub enum Address {
     FireWire(u8, [u8; 8]),
 }
 
+impl Address {
+    /// Returns the IP address, or `None` if this is a reference or a link-layer address.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Address::IPv4(_, addr) => Some(IpAddr::from(*addr)),
+            Address::IPv6(_, addr) => Some(IpAddr::from(*addr)),
+            _ => None,
+        }
+    }
+}
+
 impl From<IpAddr> for Address {
     fn from(x: IpAddr) -> Self {
         match x {
@@ -72,14 +83,60 @@ impl From<Address> for IpAddr {
     }
 }
 
+/// Addresses are represented as strings: IP addresses in their usual notation,
+/// Ethernet addresses as colon-separated hexadecimal bytes, FireWire addresses as
+/// dash-separated hexadecimal bytes (to avoid confusion with IPv6), and references as `#<id>`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Address {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        fn hex(bytes: &[u8], separator: &str) -> String {
+            let bytes: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            bytes.join(separator)
+        }
+        match self {
+            Address::Reference(id) => serializer.collect_str(&format_args!("#{}", id)),
+            Address::IPv4(_, addr) => serializer.collect_str(addr),
+            Address::IPv6(_, addr) => serializer.collect_str(addr),
+            Address::Ethernet(_, addr) => serializer.serialize_str(&hex(addr, ":")),
+            Address::FireWire(_, addr) => serializer.serialize_str(&hex(addr, "-")),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Address {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let s = String::deserialize(deserializer)?;
+        if let Some(id) = s.strip_prefix('#') {
+            return id.parse().map(Address::Reference).map_err(D::Error::custom);
+        }
+        if let Ok(addr) = s.parse::<IpAddr>() {
+            return Ok(Address::from(addr));
+        }
+        let bytes = s
+            .split([':', '-'])
+            .map(|b| u8::from_str_radix(b, 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| D::Error::custom(format!("invalid address: {}", s)))?;
+        if let Ok(addr) = <[u8; 6]>::try_from(bytes.as_slice()) {
+            Ok(Address::from(addr))
+        } else if let Ok(addr) = <[u8; 8]>::try_from(bytes.as_slice()) {
+            Ok(Address::from(addr))
+        } else {
+            Err(D::Error::custom(format!("invalid address: {}", s)))
+        }
+    }
+}
+
 impl WartsSized for Address {
     fn warts_size(&self) -> usize {
         match self {
             Address::Reference(_) => 5,
             Address::IPv4(_, _) => 6,
             Address::IPv6(_, _) => 18,
-            Address::Ethernet(_, _) => 7,
-            Address::FireWire(_, _) => 9,
+            Address::Ethernet(_, _) => 8,
+            Address::FireWire(_, _) => 10,
         }
     }
 }
@@ -101,4 +158,30 @@ mod tests {
         let addr = Ipv6Addr::new(1, 2, 3, 4, 5, 6, 7, 8);
         assert_eq!(IpAddr::from(Address::from(addr)), addr);
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_strings() {
+        let addresses = vec![
+            (Address::Reference(12), r##""#12""##),
+            (Address::from(Ipv4Addr::new(192, 0, 2, 1)), r#""192.0.2.1""#),
+            (
+                Address::from(Ipv6Addr::new(1, 2, 3, 4, 5, 6, 7, 8)),
+                r#""1:2:3:4:5:6:7:8""#,
+            ),
+            (
+                Address::from([0, 1, 2, 3, 0xab, 0xff]),
+                r#""00:01:02:03:ab:ff""#,
+            ),
+            (
+                Address::from([0, 1, 2, 3, 4, 5, 6, 7]),
+                r#""00-01-02-03-04-05-06-07""#,
+            ),
+        ];
+        for (address, expected) in addresses {
+            let json = serde_json::to_string(&address).unwrap();
+            assert_eq!(json, expected);
+            assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), address);
+        }
+    }
 }
@@ -3,15 +3,18 @@ use deku::prelude::*;
 use std::net::{Ipv4Addr, Ipv6Addr};
 
 /// A network address.
-#[derive(Copy, Clone, Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct AddressDeprecated {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
     pub id_mod: u8,
     pub address: AddressDeprecatedValue,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
 pub enum AddressDeprecatedValue {
     #[deku(id = "0x01")]
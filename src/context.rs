@@ -0,0 +1,150 @@
+use crate::{CycleStart, CycleStop, List, Measurement, Object};
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+/// A cycle, described by its start (or definition) record and by its stop record, if any.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cycle<'a> {
+    /// Cycle start or cycle definition record.
+    pub start: &'a CycleStart,
+    /// Cycle stop record, if present in the objects.
+    pub stop: Option<&'a CycleStop>,
+}
+
+impl<'a> Cycle<'a> {
+    /// Hostname at cycle start point.
+    pub fn hostname(&self) -> Option<&'a CStr> {
+        self.start.hostname.as_deref()
+    }
+
+    /// Start time of the cycle, seconds since Unix epoch.
+    pub fn start_time(&self) -> u32 {
+        self.start.start_time
+    }
+
+    /// Stop time of the cycle in seconds since Unix epoch, taken from the cycle stop record
+    /// if present, or from the cycle definition otherwise.
+    pub fn stop_time(&self) -> Option<u32> {
+        self.stop
+            .map(|stop| stop.stop_time)
+            .or(self.start.stop_time)
+    }
+}
+
+/// A measurement object along with the list and the cycle it belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WithContext<'a> {
+    /// The measurement object (traceroute, ping, ...).
+    pub object: &'a Object,
+    /// The list referenced by the measurement, if it was seen before.
+    pub list: Option<&'a List>,
+    /// The cycle referenced by the measurement, if it was seen before.
+    pub cycle: Option<Cycle<'a>>,
+}
+
+impl<'a> WithContext<'a> {
+    /// Returns the object as a [`Measurement`].
+    pub fn measurement(&self) -> &'a dyn Measurement {
+        self.object.measurement().unwrap()
+    }
+
+    /// Monitor name of the list.
+    pub fn monitor_name(&self) -> Option<&'a CStr> {
+        self.list.and_then(|list| list.monitor_name.as_deref())
+    }
+
+    /// Hostname at cycle start point.
+    pub fn hostname(&self) -> Option<&'a CStr> {
+        self.cycle.and_then(|cycle| cycle.hostname())
+    }
+}
+
+/// An iterator over the measurements of a sequence of objects, which keeps track of the
+/// lists and cycles declared along the way.
+///
+/// Objects are consumed in a single pass: a cycle stop record is attached to the measurements
+/// that follow it, and not to those that precede it.
+/// ```
+/// use warts::{ContextIter, Object};
+/// let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+/// let objects = Object::all_from_bytes(data);
+/// for m in ContextIter::new(&objects) {
+///     println!("{:?} {:?}", m.list.map(|l| &l.name), m.hostname());
+/// }
+/// ```
+pub struct ContextIter<'a, I> {
+    objects: I,
+    lists: HashMap<u32, &'a List>,
+    cycles: HashMap<u32, Cycle<'a>>,
+}
+
+impl<'a, I: Iterator<Item = &'a Object>> ContextIter<'a, I> {
+    /// Iterate over the measurements in `objects`.
+    pub fn new(objects: impl IntoIterator<IntoIter = I>) -> Self {
+        ContextIter {
+            objects: objects.into_iter(),
+            lists: HashMap::new(),
+            cycles: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a Object>> Iterator for ContextIter<'a, I> {
+    type Item = WithContext<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for object in self.objects.by_ref() {
+            match object {
+                Object::List(list) => {
+                    self.lists.insert(list.list_id, list);
+                }
+                Object::CycleStart(start) | Object::CycleDefinition(start) => {
+                    let cycle = Cycle { start, stop: None };
+                    self.cycles.insert(start.cycle_id, cycle);
+                }
+                Object::CycleStop(stop) => {
+                    if let Some(cycle) = self.cycles.get_mut(&stop.cycle_id) {
+                        cycle.stop = Some(stop);
+                    }
+                }
+                _ => {
+                    if let Some(m) = object.measurement() {
+                        return Some(WithContext {
+                            object,
+                            list: m.list_id().and_then(|id| self.lists.get(&id).copied()),
+                            cycle: m.cycle_id().and_then(|id| self.cycles.get(&id).copied()),
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextIter, Object};
+    use std::ffi::CString;
+
+    #[test]
+    fn ping_with_context() {
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        let objects = Object::all_from_bytes(data);
+        let measurements: Vec<_> = ContextIter::new(&objects).collect();
+        assert_eq!(measurements.len(), 1);
+        let m = measurements[0];
+        assert!(matches!(m.object, Object::Ping(_)));
+        assert_eq!(m.list.unwrap().name, CString::new("default").unwrap());
+        assert_eq!(m.monitor_name(), None);
+        let cycle = m.cycle.unwrap();
+        assert_eq!(
+            cycle.hostname().unwrap().to_str().unwrap(),
+            "ubuntu-linux-20-04-desktop"
+        );
+        assert_eq!(cycle.start_time(), 1644339038);
+        // The cycle stop record comes after the ping, so it is not attached yet.
+        assert_eq!(cycle.stop, None);
+        assert!(matches!(objects.last(), Some(Object::CycleStop(_))));
+    }
+}
@@ -3,9 +3,11 @@ use deku::prelude::*;
 use std::ffi::CString;
 
 /// A start record denotes the starting point for a new cycle.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct CycleStart {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
     /// Cycle ID, assigned by warts from a counter.
     pub cycle_id: u32,
@@ -16,28 +18,37 @@ pub struct CycleStart {
     /// Start time of the cycle, seconds since Unix epoch.
     pub start_time: u32,
     /// Flags.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     /// Parameter length, included if any flags are set.
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// Stop time of the cycle in seconds since Unix epoch, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
     pub stop_time: Option<u32>,
     /// Hostname at cycle start point, included if flag 2 is set.
     #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
     pub hostname: Option<CString>,
 }
 
 /// A cycle stop record denotes the end point for a cycle.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct CycleStop {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
     /// Cycle ID, assigned by warts from a counter, referencing the cycle structure that is being updated.
     pub cycle_id: u32,
     /// Stop time of the cycle, seconds since Unix epoch.
     pub stop_time: u32,
     /// Flags. Currently set to zero.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
 }
 
@@ -0,0 +1,1039 @@
+use crate::object::AddressSlot;
+use crate::{Address, Flags, ICMPExtension, IcmpKind, Measurement, Timeval, WartsSized};
+use deku::prelude::*;
+
+/// Alias resolution technique.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum DealiasMethod {
+    /// Send a probe to an unused port, and look at the source address of the reply.
+    #[deku(id = "0x01")]
+    Mercator,
+    /// Look for a shared IP-ID counter between two addresses.
+    #[deku(id = "0x02")]
+    Ally,
+    /// Look for a shared IP-ID counter among a set of addresses with round-based probing.
+    #[deku(id = "0x03")]
+    Radargun,
+    /// Find an alias of an address in a prefix.
+    #[deku(id = "0x04")]
+    Prefixscan,
+    /// Look for a shared IP-ID counter by bumping it.
+    #[deku(id = "0x05")]
+    Bump,
+    /// Monotonic ID-based alias resolution.
+    #[deku(id = "0x06")]
+    Midar,
+    /// Technique not known to this crate.
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// Outcome of an alias resolution.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum DealiasResult {
+    /// No result.
+    None = 0x00,
+    /// The addresses are aliases.
+    Aliases = 0x01,
+    /// The addresses are not aliases.
+    NotAliases = 0x02,
+    /// Halted.
+    Halted = 0x03,
+    /// The IP-ID values are echoed from the probes.
+    IpIdEcho = 0x04,
+}
+
+/// Probing method used in a probe definition.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum DealiasProbeMethod {
+    /// ICMP echo requests.
+    ICMPEcho = 0x01,
+    /// TCP ACK packets.
+    TCPAck = 0x02,
+    /// UDP packets.
+    UDP = 0x03,
+    /// TCP ACK packets with varying source port.
+    TCPAckSport = 0x04,
+    /// UDP packets with varying destination port.
+    UDPDport = 0x05,
+    /// TCP SYN packets with varying source port.
+    TCPSynSport = 0x06,
+}
+
+/// An alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct Dealias {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// List ID assigned by warts, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// Cycle ID assigned by warts, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// Time alias resolution commenced, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub start_time: Option<Timeval>,
+    /// Alias resolution technique, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub method: Option<DealiasMethod>,
+    /// Outcome of the alias resolution, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub result: Option<DealiasResult>,
+    /// Number of probes sent (16-bit, deprecated), included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub probe_count_u16: Option<u16>,
+    /// User ID assigned to the alias resolution, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub user_id: Option<u32>,
+    /// Number of probes sent, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub probe_count: Option<u32>,
+    /// Technique-specific parameters.
+    #[deku(ctx = "*method, (*length as usize + 4).saturating_sub(deku::byte_offset)")]
+    pub data: DealiasData,
+    /// Probes sent, empty if the technique is unknown.
+    #[deku(count = "data.probe_count(probe_count.or(probe_count_u16.map(u32::from)))")]
+    pub probes: Vec<DealiasProbe>,
+}
+
+/// Technique-specific parameters of an alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(
+    ctx = "endian: deku::ctx::Endian, method: Option<DealiasMethod>, remaining: usize",
+    id = "method",
+    endian = "endian"
+)]
+pub enum DealiasData {
+    #[deku(id = "Some(DealiasMethod::Mercator)")]
+    Mercator(DealiasMercator),
+    #[deku(id = "Some(DealiasMethod::Ally)")]
+    Ally(DealiasAlly),
+    #[deku(id = "Some(DealiasMethod::Radargun)")]
+    Radargun(DealiasRadargun),
+    #[deku(id = "Some(DealiasMethod::Prefixscan)")]
+    Prefixscan(DealiasPrefixscan),
+    #[deku(id = "Some(DealiasMethod::Bump)")]
+    Bump(DealiasBump),
+    #[deku(id = "Some(DealiasMethod::Midar)")]
+    Midar(DealiasMidar),
+    /// No parameters, if the method is not specified.
+    #[deku(id = "None")]
+    None,
+    /// Undecoded remainder of the object (parameters and probes), if the method is unknown.
+    #[deku(id_pat = "Some(DealiasMethod::Unknown(_))")]
+    Unknown(#[deku(count = "remaining")] Vec<u8>),
+}
+
+impl DealiasData {
+    /// Number of probes that follow the parameters: none for an unknown technique,
+    /// as they are part of the undecoded remainder.
+    fn probe_count(&self, probe_count: Option<u32>) -> u32 {
+        match self {
+            DealiasData::Unknown(_) => 0,
+            _ => probe_count.unwrap_or(0),
+        }
+    }
+}
+
+/// Parameters of a Mercator alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasMercator {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Number of attempts, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub attempts: Option<u8>,
+    /// Timeout length in seconds, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub wait_timeout: Option<u8>,
+    /// Probe definition.
+    pub probedef: DealiasProbeDef,
+}
+
+/// Parameters of an Ally alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasAlly {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Time to wait between probes in milliseconds, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub wait_probe: Option<u16>,
+    /// Timeout length in seconds, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub wait_timeout: Option<u8>,
+    /// Number of attempts, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub attempts: Option<u8>,
+    /// Maximum difference between IP-ID values, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub fudge: Option<u16>,
+    /// Ally flags, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub ally_flags: Option<u8>,
+    /// Probe definitions of the two addresses.
+    pub probedefs: [DealiasProbeDef; 2],
+}
+
+/// Parameters of a Radargun alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasRadargun {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Number of probe definitions, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub probedef_count: Option<u32>,
+    /// Number of rounds, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub attempts: Option<u16>,
+    /// Time to wait between probes in milliseconds, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub wait_probe: Option<u16>,
+    /// Time to wait between rounds in milliseconds, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub wait_round: Option<u32>,
+    /// Timeout length in seconds, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub wait_timeout: Option<u8>,
+    /// Radargun flags, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub radargun_flags: Option<u8>,
+    /// Probe definitions.
+    #[deku(count = "probedef_count.unwrap_or(0)")]
+    pub probedefs: Vec<DealiasProbeDef>,
+}
+
+/// Parameters of a Prefixscan alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasPrefixscan {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Address for which an alias is searched, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub a: Option<Address>,
+    /// Address in the prefix to scan, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub b: Option<Address>,
+    /// Alias of `a` found in the prefix, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub ab: Option<Address>,
+    /// Number of excluded addresses, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub excluded_count: Option<u16>,
+    /// Length of the prefix to scan, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub prefix: Option<u8>,
+    /// Number of attempts, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub attempts: Option<u8>,
+    /// Maximum difference between IP-ID values, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub fudge: Option<u16>,
+    /// Time to wait between probes in milliseconds, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub wait_probe: Option<u16>,
+    /// Timeout length in seconds, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub wait_timeout: Option<u8>,
+    /// Number of probe definitions, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub probedef_count: Option<u16>,
+    /// Prefixscan flags, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub prefixscan_flags: Option<u8>,
+    /// Number of replies required, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub reply_count: Option<u8>,
+    /// Addresses excluded from the scan.
+    #[deku(count = "excluded_count.unwrap_or(0)")]
+    pub excluded: Vec<Address>,
+    /// Probe definitions.
+    #[deku(count = "probedef_count.unwrap_or(0)")]
+    pub probedefs: Vec<DealiasProbeDef>,
+}
+
+/// Parameters of a Bump alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasBump {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Time to wait between probes in milliseconds, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub wait_probe: Option<u16>,
+    /// Maximum IP-ID difference to bump, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub bump_limit: Option<u16>,
+    /// Number of attempts, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub attempts: Option<u8>,
+    /// Probe definitions of the two addresses.
+    pub probedefs: [DealiasProbeDef; 2],
+}
+
+/// Parameters of a MIDAR alias resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasMidar {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Number of probe definitions, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub probedef_count: Option<u32>,
+    /// Number of rounds, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub round_count: Option<u32>,
+    /// Time to wait between probes in milliseconds, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub wait_probe: Option<u16>,
+    /// Time to wait between rounds in milliseconds, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub wait_round: Option<u32>,
+    /// Timeout length in seconds, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub wait_timeout: Option<u8>,
+    /// Probe definitions.
+    #[deku(count = "probedef_count.unwrap_or(0)")]
+    pub probedefs: Vec<DealiasProbeDef>,
+}
+
+/// A probe definition, describing the packets sent to an address.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasProbeDef {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Dst IP address ID assigned by warts, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub dst_addr_id: Option<u32>,
+    /// Src IP address ID assigned by warts, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub src_addr_id: Option<u32>,
+    /// Probe definition ID, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub id: Option<u32>,
+    /// Probing method, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub method: Option<DealiasProbeMethod>,
+    /// IP TTL of the probes, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub ttl: Option<u8>,
+    /// IP ToS of the probes, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub tos: Option<u8>,
+    /// Method-specific header values, included if flag 7 is set.
+    /// See [`DealiasProbeDef::ports`] and [`DealiasProbeDef::icmp_checksum`].
+    #[deku(cond = "flags.get(7)")]
+    pub header: Option<u32>,
+    /// TCP flags of the probes, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub tcp_flags: Option<u8>,
+    /// ICMP ID of the probes, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub icmp_id: Option<u16>,
+    /// Destination address of the probes, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub dst_addr: Option<Address>,
+    /// Source address of the probes, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub src_addr: Option<Address>,
+    /// Size of the probes, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub size: Option<u16>,
+    /// Pseudo-MTU used to elicit fragmented replies, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)")]
+    pub mtu: Option<u16>,
+}
+
+/// A probe sent during an alias resolution, and its replies.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the probe definition used, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub probedef_id: Option<u32>,
+    /// Transmit time, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub tx: Option<Timeval>,
+    /// Number of replies, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub reply_count: Option<u16>,
+    /// IP-ID of the probe, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub ipid: Option<u16>,
+    /// Sequence number of the probe, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub seq: Option<u32>,
+    /// Replies received, if any.
+    #[deku(count = "reply_count.unwrap_or(0)")]
+    pub replies: Vec<DealiasReply>,
+}
+
+/// A reply to an alias resolution probe.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct DealiasReply {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Src IP address ID assigned by warts, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub src_addr_id: Option<u32>,
+    /// Receive time, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub rx: Option<Timeval>,
+    /// IP-ID of the reply, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub ipid: Option<u16>,
+    /// IP TTL of the reply, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub ttl: Option<u8>,
+    /// ICMP type of the reply, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub icmp_type: Option<u8>,
+    /// ICMP code of the reply, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub icmp_code: Option<u8>,
+    /// TCP flags of the reply, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub tcp_flags: Option<u8>,
+    /// Quoted TTL, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub quoted_ttl: Option<u8>,
+    /// ICMP extension total length, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub icmp_extensions_length: Option<u16>,
+    /// ICMP extension objects, included if flag 8 is set.
+    #[deku(
+        cond = "flags.get(8)",
+        bytes_read = "icmp_extensions_length.unwrap_or(0)"
+    )]
+    pub icmp_extensions: Vec<ICMPExtension>,
+    /// Source address of the reply, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub src_addr: Option<Address>,
+    /// 32-bit IP-ID of the reply (IPv6 fragment header), included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub ipid32: Option<u32>,
+    /// Reply flags, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub reply_flags: Option<u8>,
+    /// Size of the reply, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub size: Option<u16>,
+}
+
+impl Dealias {
+    /// Returns `Some(true)` if the addresses were found to be aliases, `Some(false)` if they
+    /// were found not to be aliases, and `None` if the alias resolution was inconclusive.
+    pub fn is_alias(&self) -> Option<bool> {
+        match self.result? {
+            DealiasResult::Aliases => Some(true),
+            DealiasResult::NotAliases => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Probe definitions of the alias resolution.
+    pub fn probedefs(&self) -> &[DealiasProbeDef] {
+        match &self.data {
+            DealiasData::Mercator(x) => std::slice::from_ref(&x.probedef),
+            DealiasData::Ally(x) => &x.probedefs,
+            DealiasData::Radargun(x) => &x.probedefs,
+            DealiasData::Prefixscan(x) => &x.probedefs,
+            DealiasData::Bump(x) => &x.probedefs,
+            DealiasData::Midar(x) => &x.probedefs,
+            DealiasData::None | DealiasData::Unknown(_) => &[],
+        }
+    }
+
+    /// Returns the addresses found to be aliases, if any.
+    ///
+    /// For Mercator, these are the probed address and the source addresses of the replies;
+    /// for Prefixscan, the searched address and the alias found in the prefix;
+    /// and for the other techniques, the addresses of the probe definitions.
+    pub fn aliases(&self) -> Vec<Address> {
+        if self.is_alias() != Some(true) {
+            return vec![];
+        }
+        let mut aliases: Vec<Address> = match &self.data {
+            DealiasData::Mercator(x) => x
+                .probedef
+                .dst_addr
+                .into_iter()
+                .chain(
+                    self.probes
+                        .iter()
+                        .flat_map(|probe| probe.replies.iter())
+                        .filter_map(|reply| reply.src_addr),
+                )
+                .collect(),
+            DealiasData::Prefixscan(x) => x.a.into_iter().chain(x.ab).collect(),
+            _ => self
+                .probedefs()
+                .iter()
+                .filter_map(|def| def.dst_addr)
+                .collect(),
+        };
+        let mut seen = Vec::new();
+        aliases.retain(|addr| {
+            let new = !seen.contains(addr);
+            seen.push(*addr);
+            new
+        });
+        aliases
+    }
+
+    /// Address fields of the technique parameters, probe definitions and replies.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        let mut slots = Vec::new();
+        let probedefs = match &mut self.data {
+            DealiasData::Mercator(x) => std::slice::from_mut(&mut x.probedef),
+            DealiasData::Ally(x) => &mut x.probedefs[..],
+            DealiasData::Radargun(x) => &mut x.probedefs[..],
+            DealiasData::Prefixscan(x) => {
+                for address in [&mut x.a, &mut x.b, &mut x.ab] {
+                    slots.push(AddressSlot::Optional(address, None));
+                }
+                slots.extend(x.excluded.iter_mut().map(AddressSlot::Required));
+                &mut x.probedefs[..]
+            }
+            DealiasData::Bump(x) => &mut x.probedefs[..],
+            DealiasData::Midar(x) => &mut x.probedefs[..],
+            DealiasData::None | DealiasData::Unknown(_) => &mut [],
+        };
+        for def in probedefs {
+            slots.push(AddressSlot::Optional(&mut def.dst_addr, def.dst_addr_id));
+            slots.push(AddressSlot::Optional(&mut def.src_addr, def.src_addr_id));
+        }
+        for reply in self
+            .probes
+            .iter_mut()
+            .flat_map(|probe| probe.replies.iter_mut())
+        {
+            slots.push(AddressSlot::Optional(
+                &mut reply.src_addr,
+                reply.src_addr_id,
+            ));
+        }
+        slots
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        if !matches!(self.data, DealiasData::Unknown(_)) {
+            self.probe_count_u16 = None;
+            self.probe_count = Some(self.probes.len() as u32);
+        }
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.start_time);
+        push_flag!(flags, param_length, 4, self.method);
+        push_flag!(flags, param_length, 5, self.result);
+        push_flag!(flags, param_length, 6, self.probe_count_u16);
+        push_flag!(flags, param_length, 7, self.user_id);
+        push_flag!(flags, param_length, 8, self.probe_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        let probes_size: usize = self.probes.iter().map(|probe| probe.warts_size()).sum();
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.data.warts_size()
+            + probes_size) as u32;
+        self
+    }
+}
+
+impl Measurement for Dealias {
+    fn list_id(&self) -> Option<u32> {
+        self.list_id
+    }
+    fn cycle_id(&self) -> Option<u32> {
+        self.cycle_id
+    }
+    fn user_id(&self) -> Option<u32> {
+        self.user_id
+    }
+    fn start_timeval(&self) -> Option<&Timeval> {
+        self.start_time.as_ref()
+    }
+    /// Source address of the first probe definition.
+    fn src_address(&self) -> Option<&Address> {
+        self.probedefs().first()?.src_addr.as_ref()
+    }
+    /// Destination address of the first probe definition.
+    fn dst_address(&self) -> Option<&Address> {
+        self.probedefs().first()?.dst_addr.as_ref()
+    }
+}
+
+impl DealiasMercator {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.attempts);
+        push_flag!(flags, param_length, 2, self.wait_timeout);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasAlly {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.wait_probe);
+        push_flag!(flags, param_length, 2, self.wait_timeout);
+        push_flag!(flags, param_length, 3, self.attempts);
+        push_flag!(flags, param_length, 4, self.fudge);
+        push_flag!(flags, param_length, 5, self.ally_flags);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasRadargun {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.probedef_count = Some(self.probedefs.len() as u32);
+        push_flag!(flags, param_length, 1, self.probedef_count);
+        push_flag!(flags, param_length, 2, self.attempts);
+        push_flag!(flags, param_length, 3, self.wait_probe);
+        push_flag!(flags, param_length, 4, self.wait_round);
+        push_flag!(flags, param_length, 5, self.wait_timeout);
+        push_flag!(flags, param_length, 6, self.radargun_flags);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasPrefixscan {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.excluded_count = Some(self.excluded.len() as u16);
+        self.probedef_count = Some(self.probedefs.len() as u16);
+        push_flag!(flags, param_length, 1, self.a);
+        push_flag!(flags, param_length, 2, self.b);
+        push_flag!(flags, param_length, 3, self.ab);
+        push_flag!(flags, param_length, 4, self.excluded_count);
+        push_flag!(flags, param_length, 5, self.prefix);
+        push_flag!(flags, param_length, 6, self.attempts);
+        push_flag!(flags, param_length, 7, self.fudge);
+        push_flag!(flags, param_length, 8, self.wait_probe);
+        push_flag!(flags, param_length, 9, self.wait_timeout);
+        push_flag!(flags, param_length, 10, self.probedef_count);
+        push_flag!(flags, param_length, 11, self.prefixscan_flags);
+        push_flag!(flags, param_length, 12, self.reply_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasBump {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.wait_probe);
+        push_flag!(flags, param_length, 2, self.bump_limit);
+        push_flag!(flags, param_length, 3, self.attempts);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasMidar {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.probedef_count = Some(self.probedefs.len() as u32);
+        push_flag!(flags, param_length, 1, self.probedef_count);
+        push_flag!(flags, param_length, 2, self.round_count);
+        push_flag!(flags, param_length, 3, self.wait_probe);
+        push_flag!(flags, param_length, 4, self.wait_round);
+        push_flag!(flags, param_length, 5, self.wait_timeout);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasProbeDef {
+    /// Source and destination ports of UDP and TCP probes.
+    pub fn ports(&self) -> Option<(u16, u16)> {
+        match self.method? {
+            DealiasProbeMethod::ICMPEcho => None,
+            _ => self.header.map(|x| ((x >> 16) as u16, x as u16)),
+        }
+    }
+
+    /// ICMP checksum of ICMP echo probes.
+    pub fn icmp_checksum(&self) -> Option<u16> {
+        match self.method? {
+            DealiasProbeMethod::ICMPEcho => self.header.map(|x| (x >> 16) as u16),
+            _ => None,
+        }
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.dst_addr_id);
+        push_flag!(flags, param_length, 2, self.src_addr_id);
+        push_flag!(flags, param_length, 3, self.id);
+        push_flag!(flags, param_length, 4, self.method);
+        push_flag!(flags, param_length, 5, self.ttl);
+        push_flag!(flags, param_length, 6, self.tos);
+        push_flag!(flags, param_length, 7, self.header);
+        push_flag!(flags, param_length, 8, self.tcp_flags);
+        push_flag!(flags, param_length, 9, self.icmp_id);
+        push_flag!(flags, param_length, 10, self.dst_addr);
+        push_flag!(flags, param_length, 11, self.src_addr);
+        push_flag!(flags, param_length, 12, self.size);
+        push_flag!(flags, param_length, 13, self.mtu);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasProbe {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.reply_count = Some(self.replies.len() as u16);
+        push_flag!(flags, param_length, 1, self.probedef_id);
+        push_flag!(flags, param_length, 2, self.tx);
+        push_flag!(flags, param_length, 3, self.reply_count);
+        push_flag!(flags, param_length, 4, self.ipid);
+        push_flag!(flags, param_length, 5, self.seq);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl DealiasReply {
+    /// Classifies the ICMP message received for a dealiasing probe, according to the family
+    /// of its source address; `None` for non-ICMP replies or an undereferenced source.
+    pub fn icmp_kind(&self) -> Option<IcmpKind> {
+        IcmpKind::from_reply(self.icmp_type, self.icmp_code, self.src_addr.as_ref())
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.src_addr_id);
+        push_flag!(flags, param_length, 2, self.rx);
+        push_flag!(flags, param_length, 3, self.ipid);
+        push_flag!(flags, param_length, 4, self.ttl);
+        push_flag!(flags, param_length, 5, self.icmp_type);
+        push_flag!(flags, param_length, 5, self.icmp_code);
+        push_flag!(flags, param_length, 6, self.tcp_flags);
+        push_flag!(flags, param_length, 7, self.quoted_ttl);
+        self.icmp_extensions_length =
+            (!self.icmp_extensions.is_empty()).then(|| self.icmp_extensions.warts_size() as u16);
+        push_flag!(flags, param_length, 8, self.icmp_extensions_length);
+        param_length += self.icmp_extensions.warts_size();
+        push_flag!(flags, param_length, 9, self.src_addr);
+        push_flag!(flags, param_length, 10, self.ipid32);
+        push_flag!(flags, param_length, 11, self.reply_flags);
+        push_flag!(flags, param_length, 12, self.size);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for DealiasMethod {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for DealiasResult {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for DealiasProbeMethod {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for DealiasData {
+    fn warts_size(&self) -> usize {
+        fn probedefs_size(probedefs: &[DealiasProbeDef]) -> usize {
+            probedefs.iter().map(|def| def.warts_size()).sum()
+        }
+        fn params_size(flags: &Flags, param_length: &Option<u16>) -> usize {
+            flags.warts_size() + param_length.warts_size() + param_length.unwrap_or(0) as usize
+        }
+        match self {
+            DealiasData::Mercator(x) => {
+                params_size(&x.flags, &x.param_length) + x.probedef.warts_size()
+            }
+            DealiasData::Ally(x) => {
+                params_size(&x.flags, &x.param_length) + probedefs_size(&x.probedefs)
+            }
+            DealiasData::Radargun(x) => {
+                params_size(&x.flags, &x.param_length) + probedefs_size(&x.probedefs)
+            }
+            DealiasData::Prefixscan(x) => {
+                let excluded_size: usize = x.excluded.iter().map(|addr| addr.warts_size()).sum();
+                params_size(&x.flags, &x.param_length)
+                    + excluded_size
+                    + probedefs_size(&x.probedefs)
+            }
+            DealiasData::Bump(x) => {
+                params_size(&x.flags, &x.param_length) + probedefs_size(&x.probedefs)
+            }
+            DealiasData::Midar(x) => {
+                params_size(&x.flags, &x.param_length) + probedefs_size(&x.probedefs)
+            }
+            DealiasData::None => 0,
+            DealiasData::Unknown(x) => x.len(),
+        }
+    }
+}
+
+impl WartsSized for DealiasProbeDef {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+impl WartsSized for DealiasProbe {
+    fn warts_size(&self) -> usize {
+        let replies_size: usize = self.replies.iter().map(|reply| reply.warts_size()).sum();
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+            + replies_size
+    }
+}
+
+impl WartsSized for DealiasReply {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, Dealias, DealiasData, DealiasMercator, DealiasMethod, DealiasProbe,
+        DealiasProbeDef, DealiasProbeMethod, DealiasReply, DealiasResult, Object, Timeval,
+    };
+    use deku::DekuContainerWrite;
+    use std::net::Ipv4Addr;
+
+    fn probedef() -> DealiasProbeDef {
+        DealiasProbeDef {
+            flags: Default::default(),
+            param_length: None,
+            dst_addr_id: None,
+            src_addr_id: None,
+            id: Some(0),
+            method: Some(DealiasProbeMethod::UDP),
+            ttl: Some(255),
+            tos: None,
+            header: Some(0xe000_8235),
+            tcp_flags: None,
+            icmp_id: None,
+            dst_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 100))),
+            size: Some(28),
+            mtu: None,
+        }
+        .finalize()
+    }
+
+    fn reply() -> DealiasReply {
+        DealiasReply {
+            flags: Default::default(),
+            param_length: None,
+            src_addr_id: None,
+            rx: Some(Timeval::new(1644339038, 2000)),
+            ipid: Some(1234),
+            ttl: Some(60),
+            icmp_type: Some(3),
+            icmp_code: Some(3),
+            tcp_flags: None,
+            quoted_ttl: None,
+            icmp_extensions_length: None,
+            icmp_extensions: vec![],
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 2))),
+            ipid32: None,
+            reply_flags: None,
+            size: Some(56),
+        }
+        .finalize()
+    }
+
+    fn mercator(reply: DealiasReply) -> Dealias {
+        let probe = DealiasProbe {
+            flags: Default::default(),
+            param_length: None,
+            probedef_id: Some(0),
+            tx: Some(Timeval::new(1644339038, 0)),
+            reply_count: None,
+            ipid: Some(42),
+            seq: None,
+            replies: vec![reply],
+        }
+        .finalize();
+        let mercator = DealiasMercator {
+            flags: Default::default(),
+            param_length: None,
+            attempts: Some(3),
+            wait_timeout: Some(5),
+            probedef: probedef(),
+        }
+        .finalize();
+        Dealias {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            method: Some(DealiasMethod::Mercator),
+            result: Some(DealiasResult::Aliases),
+            probe_count_u16: None,
+            user_id: None,
+            probe_count: None,
+            data: DealiasData::Mercator(mercator),
+            probes: vec![probe],
+        }
+        .finalize()
+    }
+
+    #[test]
+    fn mercator_round_trip() {
+        let dealias = mercator(reply());
+        assert_eq!(dealias.probedefs()[0].ports(), Some((0xe000, 0x8235)));
+        assert_eq!(
+            dealias.aliases(),
+            vec![
+                Address::from(Ipv4Addr::new(192, 0, 2, 1)),
+                Address::from(Ipv4Addr::new(192, 0, 2, 2))
+            ]
+        );
+
+        let bytes = Object::Dealias(dealias.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + dealias.length as usize);
+        let objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::Dealias(dealias)]);
+    }
+
+    #[test]
+    fn dereference() {
+        let mut reply = reply();
+        reply.src_addr = Some(Address::Reference(0));
+        let mut object = Object::Dealias(mercator(reply.finalize()));
+        object.dereference();
+        let m = object.measurement().unwrap();
+        assert_eq!(m.src_addr(), Some(Ipv4Addr::new(192, 0, 2, 100).into()));
+        assert_eq!(m.dst_addr(), Some(Ipv4Addr::new(192, 0, 2, 1).into()));
+        let Object::Dealias(dealias) = object else {
+            unreachable!()
+        };
+        assert_eq!(
+            dealias.probes[0].replies[0].src_addr,
+            Some(Address::from(Ipv4Addr::new(192, 0, 2, 1)))
+        );
+    }
+
+    #[test]
+    fn unknown_method() {
+        // Flag 4 (method) set, followed by parameters of an unknown technique.
+        let bytes = [
+            0x12, 0x05, 0x00, 0x09, 0x00, 0x00, 0x00, 0x07, 0x08, 0x00, 0x01, 0x7f, 0x01, 0x02,
+            0x03,
+        ];
+        let objects = Object::all_from_bytes(&bytes);
+        let Object::Dealias(dealias) = &objects[0] else {
+            panic!("expected an alias resolution");
+        };
+        assert_eq!(dealias.method, Some(DealiasMethod::Unknown(0x7f)));
+        assert_eq!(dealias.data, DealiasData::Unknown(vec![0x01, 0x02, 0x03]));
+        assert!(dealias.probes.is_empty());
+        assert_eq!(objects[0].to_bytes().unwrap(), bytes);
+    }
+}
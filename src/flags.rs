@@ -28,7 +28,7 @@ use std::fmt::{Debug, Formatter};
 /// > The rest of each byte is used to record flags, whose position in the sequence signifies if a particular parameter is included.
 /// > For example, if bit 6 of byte zero is set, then parameter 6 is included, and if bit 5 of byte one is set,
 /// > then parameter 12 is included, and if bit 2 of byte two is set, then parameter 16 is included.
-#[derive(PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Flags {
     /// A bitfield where bit `i` is set to 1 if flag `i` is set.
     value: u64,
@@ -62,7 +62,7 @@ impl Flags {
         let mut buf = [0u8; 8];
         let mut value = self.value;
         let mut index = 0;
-        while value > 0x80 {
+        while value >= 0x80 {
             buf[index] = (0x80 | value) as u8;
             index += 1;
             value >>= 7;
@@ -72,6 +72,11 @@ impl Flags {
         Vec::from(&buf[..index])
     }
 
+    /// Returns the underlying 64-bit bitfield.
+    pub fn bits(&self) -> u64 {
+        self.value
+    }
+
     /// Returns true if at-least one flag is set to 1.
     pub fn any(&self) -> bool {
         self.value > 0
@@ -134,6 +139,32 @@ impl Default for Flags {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Flags {
+    /// Flags are represented as the list of the indices of the flags set to 1.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((1..=64).filter(|&index| self.get(index)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Flags {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let indices = Vec::<u8>::deserialize(deserializer)?;
+        if let Some(index) = indices.iter().find(|&&index| index == 0 || index > 64) {
+            return Err(serde::de::Error::custom(format!(
+                "invalid flag index {}, expected a value between 1 and 64",
+                index
+            )));
+        }
+        Ok(Flags::new(
+            indices
+                .iter()
+                .fold(0, |value, index| value | 1 << (index - 1)),
+        ))
+    }
+}
+
 impl WartsSized for Flags {
     fn warts_size(&self) -> usize {
         // TODO: Better implementation...
@@ -146,6 +177,13 @@ mod tests {
     use crate::Flags;
     use deku::bitvec::{bitvec, Msb0};
 
+    #[test]
+    fn eighth_flag() {
+        let flags = Flags::from(vec![8]);
+        assert_eq!(flags.to_vec(), vec![0x80, 0x01]);
+        assert_eq!(Flags::from_slice(&flags.to_vec()), (2, flags));
+    }
+
     #[test]
     fn single_byte_without_flags() {
         let bitslice = bitvec![u8, Msb0; 0, 0, 0, 0, 0, 0, 0, 0];
@@ -178,6 +216,16 @@ mod tests {
         assert!(!flags.get(15));
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_indices() {
+        let flags = Flags::from(vec![1, 7, 8, 14]);
+        let json = serde_json::to_string(&flags).unwrap();
+        assert_eq!(json, "[1,7,8,14]");
+        assert_eq!(serde_json::from_str::<Flags>(&json).unwrap(), flags);
+        assert!(serde_json::from_str::<Flags>("[0]").is_err());
+    }
+
     #[test]
     fn from_int_flags() {
         let flags = Flags::from(vec![1, 7, 8, 14]);
@@ -0,0 +1,666 @@
+use crate::object::AddressSlot;
+use crate::{Address, Flags, Timeval, WartsSized};
+use deku::prelude::*;
+use std::ffi::{CStr, CString};
+use std::net::IpAddr;
+
+/// DNS record type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u16")]
+pub enum HostRecordType {
+    #[deku(id = "1")]
+    A,
+    #[deku(id = "2")]
+    NS,
+    #[deku(id = "5")]
+    CNAME,
+    #[deku(id = "6")]
+    SOA,
+    #[deku(id = "12")]
+    PTR,
+    #[deku(id = "15")]
+    MX,
+    #[deku(id = "16")]
+    TXT,
+    #[deku(id = "28")]
+    AAAA,
+    /// A record type unknown to this library.
+    #[deku(id_pat = "_")]
+    Unknown(u16),
+}
+
+/// Reason why a DNS measurement stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum HostStopReason {
+    #[deku(id = "0x00")]
+    None,
+    /// A response was received.
+    #[deku(id = "0x01")]
+    Done,
+    #[deku(id = "0x02")]
+    Timeout,
+    #[deku(id = "0x03")]
+    Halted,
+    #[deku(id = "0x04")]
+    Error,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// A DNS measurement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct Host {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the list the DNS measurement was issued from, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// ID of the cycle the DNS measurement is part of, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// User-supplied identifier of the DNS measurement, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Local address the queries are sent from, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub src_addr: Option<Address>,
+    /// Address of the DNS server, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub dst_addr: Option<Address>,
+    /// Time measurement commenced, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub start_time: Option<Timeval>,
+    /// Measurement flags, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub host_flags: Option<u16>,
+    /// Time to wait for a response in milliseconds, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub wait: Option<u16>,
+    /// Reason why the measurement stopped, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub stop_reason: Option<HostStopReason>,
+    /// Number of retries, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub retries: Option<u8>,
+    /// Query type, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub query_type: Option<HostRecordType>,
+    /// Query class, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub query_class: Option<u16>,
+    /// Query name, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub query_name: Option<CString>,
+    /// Number of queries sent, included if flag 14 is set.
+    #[deku(cond = "flags.get(14)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub query_count: Option<u8>,
+    /// Queries sent.
+    #[deku(count = "query_count.unwrap_or(0)")]
+    pub queries: Vec<HostQuery>,
+}
+
+/// A DNS query and its response.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct HostQuery {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Transmit time, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub tx: Option<Timeval>,
+    /// Receive time, included if a response was received and flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub rx: Option<Timeval>,
+    /// Response code, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub rcode: Option<u8>,
+    /// Response flags, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub response_flags: Option<u8>,
+    /// Query ID, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub id: Option<u16>,
+    /// Number of answer records, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub answer_count: Option<u16>,
+    /// Number of authority records, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub authority_count: Option<u16>,
+    /// Number of additional records, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub additional_count: Option<u16>,
+    /// Answer section of the response.
+    #[deku(count = "answer_count.unwrap_or(0)")]
+    pub answers: Vec<HostRecord>,
+    /// Authority section of the response.
+    #[deku(count = "authority_count.unwrap_or(0)")]
+    pub authority: Vec<HostRecord>,
+    /// Additional section of the response.
+    #[deku(count = "additional_count.unwrap_or(0)")]
+    pub additional: Vec<HostRecord>,
+}
+
+/// A DNS resource record.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct HostRecord {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Record class, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub class: Option<u16>,
+    /// Record type, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub record_type: Option<HostRecordType>,
+    /// Owner name, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub name: Option<CString>,
+    /// Time to live, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub ttl: Option<u32>,
+    /// Encoding of the record data, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub data_type: Option<u16>,
+    /// Record data.
+    #[deku(ctx = "*data_type")]
+    pub data: HostRecordData,
+}
+
+/// Data of a DNS resource record, as encoded in warts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(
+    ctx = "endian: deku::ctx::Endian, data_type: Option<u16>",
+    id = "data_type",
+    endian = "endian"
+)]
+pub enum HostRecordData {
+    /// Address, for A and AAAA records.
+    #[deku(id = "Some(1)")]
+    Address(Address),
+    /// Domain name, for PTR, CNAME and NS records.
+    #[deku(id = "Some(2)")]
+    Name(#[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::cstring"))] CString),
+    #[deku(id = "Some(3)")]
+    SOA(HostSoa),
+    #[deku(id = "Some(4)")]
+    MX(HostMx),
+    #[deku(id = "Some(5)")]
+    TXT(HostTxt),
+    /// No data, or data that is not decoded by scamper.
+    #[deku(id_pat = "_")]
+    None,
+}
+
+/// Data of a SOA record.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct HostSoa {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Primary name server, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub mname: Option<CString>,
+    /// Mailbox of the person responsible for the zone, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub rname: Option<CString>,
+    /// Included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub serial: Option<u32>,
+    /// Included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub refresh: Option<u32>,
+    /// Included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub retry: Option<u32>,
+    /// Included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub expire: Option<u32>,
+    /// Included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub minimum: Option<u32>,
+}
+
+/// Data of a MX record.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct HostMx {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub preference: Option<u16>,
+    /// Included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub exchange: Option<CString>,
+}
+
+/// Data of a TXT record.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct HostTxt {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Number of strings, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub string_count: Option<u16>,
+    /// Character strings.
+    #[deku(count = "string_count.unwrap_or(0)")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::vec_cstring"))]
+    pub strings: Vec<CString>,
+}
+
+/// Typed view of the data of a DNS resource record, see [`HostRecord::rdata`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RecordData<'a> {
+    A(IpAddr),
+    AAAA(IpAddr),
+    NS(&'a CStr),
+    CNAME(&'a CStr),
+    PTR(&'a CStr),
+    MX(&'a HostMx),
+    SOA(&'a HostSoa),
+    TXT(&'a [CString]),
+}
+
+impl Host {
+    /// Source and server addresses, then the addresses of the A and AAAA records
+    /// of each query, section by section.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        let mut slots = vec![
+            AddressSlot::Optional(&mut self.src_addr, None),
+            AddressSlot::Optional(&mut self.dst_addr, None),
+        ];
+        for query in self.queries.iter_mut() {
+            let records = query
+                .answers
+                .iter_mut()
+                .chain(query.authority.iter_mut())
+                .chain(query.additional.iter_mut());
+            for record in records {
+                if let HostRecordData::Address(address) = &mut record.data {
+                    slots.push(AddressSlot::Required(address));
+                }
+            }
+        }
+        slots
+    }
+
+    /// Returns the answer records of all the queries.
+    pub fn answers(&self) -> impl Iterator<Item = &HostRecord> {
+        self.queries.iter().flat_map(|query| query.answers.iter())
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.query_count = Some(self.queries.len() as u8);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.src_addr);
+        push_flag!(flags, param_length, 5, self.dst_addr);
+        push_flag!(flags, param_length, 6, self.start_time);
+        push_flag!(flags, param_length, 7, self.host_flags);
+        push_flag!(flags, param_length, 8, self.wait);
+        push_flag!(flags, param_length, 9, self.stop_reason);
+        push_flag!(flags, param_length, 10, self.retries);
+        push_flag!(flags, param_length, 11, self.query_type);
+        push_flag!(flags, param_length, 12, self.query_class);
+        push_flag!(flags, param_length, 13, self.query_name);
+        push_flag!(flags, param_length, 14, self.query_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.queries.warts_size()) as u32;
+        self
+    }
+}
+
+impl_measurement!(Host);
+
+impl HostQuery {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.answer_count = Some(self.answers.len() as u16);
+        self.authority_count = Some(self.authority.len() as u16);
+        self.additional_count = Some(self.additional.len() as u16);
+        push_flag!(flags, param_length, 1, self.tx);
+        push_flag!(flags, param_length, 2, self.rx);
+        push_flag!(flags, param_length, 3, self.rcode);
+        push_flag!(flags, param_length, 4, self.response_flags);
+        push_flag!(flags, param_length, 5, self.id);
+        push_flag!(flags, param_length, 6, self.answer_count);
+        push_flag!(flags, param_length, 7, self.authority_count);
+        push_flag!(flags, param_length, 8, self.additional_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl HostRecord {
+    /// Returns the record data according to the record type, or `None` if the record type
+    /// is not supported or does not match the data.
+    pub fn rdata(&self) -> Option<RecordData<'_>> {
+        match (self.record_type?, &self.data) {
+            (HostRecordType::A, HostRecordData::Address(addr)) => Some(RecordData::A(addr.ip()?)),
+            (HostRecordType::AAAA, HostRecordData::Address(addr)) => {
+                Some(RecordData::AAAA(addr.ip()?))
+            }
+            (HostRecordType::NS, HostRecordData::Name(name)) => Some(RecordData::NS(name)),
+            (HostRecordType::CNAME, HostRecordData::Name(name)) => Some(RecordData::CNAME(name)),
+            (HostRecordType::PTR, HostRecordData::Name(name)) => Some(RecordData::PTR(name)),
+            (HostRecordType::MX, HostRecordData::MX(mx)) => Some(RecordData::MX(mx)),
+            (HostRecordType::SOA, HostRecordData::SOA(soa)) => Some(RecordData::SOA(soa)),
+            (HostRecordType::TXT, HostRecordData::TXT(txt)) => Some(RecordData::TXT(&txt.strings)),
+            _ => None,
+        }
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.data = self.data.finalize();
+        self.data_type = self.data.data_type();
+        push_flag!(flags, param_length, 1, self.class);
+        push_flag!(flags, param_length, 2, self.record_type);
+        push_flag!(flags, param_length, 3, self.name);
+        push_flag!(flags, param_length, 4, self.ttl);
+        push_flag!(flags, param_length, 5, self.data_type);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl HostRecordData {
+    fn data_type(&self) -> Option<u16> {
+        match self {
+            HostRecordData::Address(_) => Some(1),
+            HostRecordData::Name(_) => Some(2),
+            HostRecordData::SOA(_) => Some(3),
+            HostRecordData::MX(_) => Some(4),
+            HostRecordData::TXT(_) => Some(5),
+            HostRecordData::None => None,
+        }
+    }
+
+    pub fn finalize(self) -> Self {
+        match self {
+            HostRecordData::SOA(x) => HostRecordData::SOA(x.finalize()),
+            HostRecordData::MX(x) => HostRecordData::MX(x.finalize()),
+            HostRecordData::TXT(x) => HostRecordData::TXT(x.finalize()),
+            x => x,
+        }
+    }
+}
+
+impl HostSoa {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.mname);
+        push_flag!(flags, param_length, 2, self.rname);
+        push_flag!(flags, param_length, 3, self.serial);
+        push_flag!(flags, param_length, 4, self.refresh);
+        push_flag!(flags, param_length, 5, self.retry);
+        push_flag!(flags, param_length, 6, self.expire);
+        push_flag!(flags, param_length, 7, self.minimum);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl HostMx {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.preference);
+        push_flag!(flags, param_length, 2, self.exchange);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl HostTxt {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.string_count = Some(self.strings.len() as u16);
+        push_flag!(flags, param_length, 1, self.string_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for HostRecordType {
+    fn warts_size(&self) -> usize {
+        2
+    }
+}
+
+impl WartsSized for HostStopReason {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+fn params_size(flags: &Flags, param_length: &Option<u16>) -> usize {
+    flags.warts_size() + param_length.warts_size() + param_length.unwrap_or(0) as usize
+}
+
+impl WartsSized for HostQuery {
+    fn warts_size(&self) -> usize {
+        params_size(&self.flags, &self.param_length)
+            + self.answers.warts_size()
+            + self.authority.warts_size()
+            + self.additional.warts_size()
+    }
+}
+
+impl WartsSized for HostRecord {
+    fn warts_size(&self) -> usize {
+        params_size(&self.flags, &self.param_length) + self.data.warts_size()
+    }
+}
+
+impl WartsSized for HostRecordData {
+    fn warts_size(&self) -> usize {
+        match self {
+            HostRecordData::Address(x) => x.warts_size(),
+            HostRecordData::Name(x) => x.warts_size(),
+            HostRecordData::SOA(x) => params_size(&x.flags, &x.param_length),
+            HostRecordData::MX(x) => params_size(&x.flags, &x.param_length),
+            HostRecordData::TXT(x) => {
+                params_size(&x.flags, &x.param_length) + x.strings.warts_size()
+            }
+            HostRecordData::None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, Host, HostMx, HostQuery, HostRecord, HostRecordData, HostRecordType,
+        HostStopReason, HostTxt, Object, RecordData, Timeval,
+    };
+    use deku::DekuContainerWrite;
+    use std::ffi::CString;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn record(record_type: HostRecordType, data: HostRecordData) -> HostRecord {
+        HostRecord {
+            flags: Default::default(),
+            param_length: None,
+            class: Some(1),
+            record_type: Some(record_type),
+            name: Some(CString::new("example.org").unwrap()),
+            ttl: Some(3600),
+            data_type: None,
+            data,
+        }
+        .finalize()
+    }
+
+    #[test]
+    fn round_trip() {
+        let answers = vec![
+            record(
+                HostRecordType::A,
+                HostRecordData::Address(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            ),
+            record(
+                HostRecordType::PTR,
+                HostRecordData::Name(CString::new("host.example.org").unwrap()),
+            ),
+            record(
+                HostRecordType::MX,
+                HostRecordData::MX(HostMx {
+                    flags: Default::default(),
+                    param_length: None,
+                    preference: Some(10),
+                    exchange: Some(CString::new("mail.example.org").unwrap()),
+                }),
+            ),
+            record(
+                HostRecordType::TXT,
+                HostRecordData::TXT(HostTxt {
+                    flags: Default::default(),
+                    param_length: None,
+                    string_count: None,
+                    strings: vec![CString::new("v=spf1 -all").unwrap()],
+                }),
+            ),
+        ];
+        let query = HostQuery {
+            flags: Default::default(),
+            param_length: None,
+            tx: Some(Timeval::new(1644339038, 0)),
+            rx: Some(Timeval::new(1644339038, 15000)),
+            rcode: Some(0),
+            response_flags: Some(0x80),
+            id: Some(0x1234),
+            answer_count: None,
+            authority_count: None,
+            additional_count: None,
+            answers,
+            authority: vec![],
+            // Refers to the address of the first answer, as written by scamper.
+            additional: vec![record(
+                HostRecordType::A,
+                HostRecordData::Address(Address::Reference(2)),
+            )],
+        }
+        .finalize();
+        let host = Host {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 100))),
+            dst_addr: Some(Address::from(Ipv4Addr::new(8, 8, 8, 8))),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            host_flags: None,
+            wait: Some(5000),
+            stop_reason: Some(HostStopReason::Done),
+            retries: Some(0),
+            query_type: Some(HostRecordType::A),
+            query_class: Some(1),
+            query_name: Some(CString::new("example.org").unwrap()),
+            query_count: None,
+            queries: vec![query],
+        }
+        .finalize();
+        let bytes = Object::Host(host.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + host.length as usize);
+        let mut objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::Host(host.clone())]);
+        objects[0].dereference();
+        let Object::Host(dereferenced) = &objects[0] else {
+            unreachable!()
+        };
+        assert_eq!(
+            dereferenced.queries[0].additional[0].rdata(),
+            Some(RecordData::A(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))))
+        );
+
+        let rdata: Vec<_> = host.answers().filter_map(|rr| rr.rdata()).collect();
+        assert_eq!(rdata.len(), 4);
+        assert_eq!(
+            rdata[0],
+            RecordData::A(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))
+        );
+        assert!(
+            matches!(rdata[1], RecordData::PTR(name) if name.to_str() == Ok("host.example.org"))
+        );
+        assert!(matches!(rdata[2], RecordData::MX(mx) if mx.preference == Some(10)));
+        assert!(matches!(rdata[3], RecordData::TXT(strings) if strings.len() == 1));
+    }
+}
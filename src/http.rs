@@ -0,0 +1,438 @@
+use crate::object::AddressSlot;
+use crate::{Address, Flags, Timeval, WartsSized};
+use deku::prelude::*;
+use std::ffi::CString;
+
+/// Scheme of an HTTP measurement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum HttpType {
+    #[deku(id = "0x00")]
+    HTTP,
+    #[deku(id = "0x01")]
+    HTTPS,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// Reason why an HTTP measurement stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum HttpStopReason {
+    #[deku(id = "0x00")]
+    None,
+    #[deku(id = "0x01")]
+    Halted,
+    /// The connection could not be established.
+    #[deku(id = "0x02")]
+    NoConnection,
+    /// The response was fully received.
+    #[deku(id = "0x03")]
+    Done,
+    #[deku(id = "0x04")]
+    Error,
+    #[deku(id = "0x05")]
+    Timeout,
+    /// The TLS certificate could not be verified.
+    #[deku(id = "0x06")]
+    Insecure,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// Direction of a buffer recorded during an HTTP measurement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum HttpBufferDirection {
+    /// Data sent by scamper.
+    Transmitted = 0x01,
+    /// Data received by scamper.
+    Received = 0x02,
+}
+
+/// Content of a buffer recorded during an HTTP measurement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum HttpBufferType {
+    /// TLS handshake.
+    TLS = 0x01,
+    /// HTTP header.
+    Header = 0x02,
+    /// HTTP body.
+    Data = 0x03,
+}
+
+/// An HTTP measurement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct Http {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the list the request was made for, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// ID of the cycle in which the request was made, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// User-supplied identifier of the request, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Local address of the connection to the web server, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub src_addr: Option<Address>,
+    /// Address of the web server, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub dst_addr: Option<Address>,
+    /// Source port, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub src_port: Option<u16>,
+    /// Destination port, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub dst_port: Option<u16>,
+    /// Time measurement commenced, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub start_time: Option<Timeval>,
+    /// RTT of the TCP handshake in microseconds, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub handshake_rtt: Option<u32>,
+    /// Maximum duration of the measurement, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub max_time: Option<Timeval>,
+    /// Measurement flags, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub http_flags: Option<u32>,
+    /// Reason why the measurement stopped, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub stop_reason: Option<HttpStopReason>,
+    /// HTTP or HTTPS, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)")]
+    pub http_type: Option<HttpType>,
+    /// Host requested, included if flag 14 is set.
+    #[deku(cond = "flags.get(14)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub host: Option<CString>,
+    /// File requested, included if flag 15 is set.
+    #[deku(cond = "flags.get(15)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub file: Option<CString>,
+    /// Number of buffers recorded, included if flag 16 is set.
+    #[deku(cond = "flags.get(16)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub buffer_count: Option<u32>,
+    /// Data sent and received during the measurement, in order.
+    #[deku(count = "buffer_count.unwrap_or(0)")]
+    pub buffers: Vec<HttpBuffer>,
+}
+
+/// Data sent or received during an HTTP measurement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct HttpBuffer {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Direction of the data, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub direction: Option<HttpBufferDirection>,
+    /// Content of the data, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub buffer_type: Option<HttpBufferType>,
+    /// Time the data was sent or received, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub timestamp: Option<Timeval>,
+    /// Length of the data, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub data_length: Option<u16>,
+    /// Bytes sent or received, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)", count = "data_length.unwrap_or(0)")]
+    pub data: Option<Vec<u8>>,
+}
+
+/// Status line of an HTTP response.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HttpStatus {
+    /// Protocol version, e.g. `HTTP/1.1`.
+    pub version: String,
+    /// Status code, e.g. 200.
+    pub code: u16,
+    /// Reason phrase, e.g. `OK`.
+    pub reason: String,
+}
+
+impl Http {
+    /// Local and web server addresses, the only addresses of an HTTP measurement.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        vec![
+            AddressSlot::Optional(&mut self.src_addr, None),
+            AddressSlot::Optional(&mut self.dst_addr, None),
+        ]
+    }
+
+    fn concat(&self, direction: HttpBufferDirection, buffer_type: HttpBufferType) -> Vec<u8> {
+        self.buffers
+            .iter()
+            .filter(|buf| buf.direction == Some(direction) && buf.buffer_type == Some(buffer_type))
+            .flat_map(|buf| buf.data.iter().flatten().copied())
+            .collect()
+    }
+
+    /// Header of the request, as sent.
+    pub fn request_header(&self) -> Vec<u8> {
+        self.concat(HttpBufferDirection::Transmitted, HttpBufferType::Header)
+    }
+
+    /// Header of the response, as received.
+    pub fn response_header(&self) -> Vec<u8> {
+        self.concat(HttpBufferDirection::Received, HttpBufferType::Header)
+    }
+
+    /// Body of the response, as received.
+    pub fn response_body(&self) -> Vec<u8> {
+        self.concat(HttpBufferDirection::Received, HttpBufferType::Data)
+    }
+
+    /// Parses the status line of the response header.
+    pub fn status(&self) -> Option<HttpStatus> {
+        let header = self.response_header();
+        let line = String::from_utf8_lossy(&header).lines().next()?.to_string();
+        let mut parts = line.splitn(3, ' ');
+        let version = parts.next()?;
+        if !version.starts_with("HTTP/") {
+            return None;
+        }
+        Some(HttpStatus {
+            version: version.to_string(),
+            code: parts.next()?.parse().ok()?,
+            reason: parts.next().unwrap_or("").trim_end().to_string(),
+        })
+    }
+
+    /// Parses the header fields of the response header, in order.
+    /// Field names are returned as-is, and values are trimmed.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        let header = self.response_header();
+        String::from_utf8_lossy(&header)
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                Some((name.to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns the value of the first response header field with the given name,
+    /// compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.headers()
+            .into_iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.buffer_count = Some(self.buffers.len() as u32);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.src_addr);
+        push_flag!(flags, param_length, 5, self.dst_addr);
+        push_flag!(flags, param_length, 6, self.src_port);
+        push_flag!(flags, param_length, 7, self.dst_port);
+        push_flag!(flags, param_length, 8, self.start_time);
+        push_flag!(flags, param_length, 9, self.handshake_rtt);
+        push_flag!(flags, param_length, 10, self.max_time);
+        push_flag!(flags, param_length, 11, self.http_flags);
+        push_flag!(flags, param_length, 12, self.stop_reason);
+        push_flag!(flags, param_length, 13, self.http_type);
+        push_flag!(flags, param_length, 14, self.host);
+        push_flag!(flags, param_length, 15, self.file);
+        push_flag!(flags, param_length, 16, self.buffer_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.buffers.warts_size()) as u32;
+        self
+    }
+}
+
+impl_measurement!(Http);
+
+impl HttpBuffer {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.data_length = self.data.as_ref().map(|data| data.len() as u16);
+        push_flag!(flags, param_length, 1, self.direction);
+        push_flag!(flags, param_length, 2, self.buffer_type);
+        push_flag!(flags, param_length, 3, self.timestamp);
+        push_flag!(flags, param_length, 4, self.data_length);
+        push_flag!(flags, param_length, 5, self.data);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for HttpType {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for HttpStopReason {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for HttpBufferDirection {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for HttpBufferType {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for HttpBuffer {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, Http, HttpBuffer, HttpBufferDirection, HttpBufferType, HttpStatus, HttpStopReason,
+        HttpType, Object, Timeval,
+    };
+    use deku::bitvec::BitView;
+    use deku::prelude::*;
+    use std::ffi::CString;
+    use std::net::Ipv4Addr;
+
+    fn buffer(
+        direction: HttpBufferDirection,
+        buffer_type: HttpBufferType,
+        data: &[u8],
+    ) -> HttpBuffer {
+        HttpBuffer {
+            flags: Default::default(),
+            param_length: None,
+            direction: Some(direction),
+            buffer_type: Some(buffer_type),
+            timestamp: Some(Timeval::new(1644339038, 0)),
+            data_length: None,
+            data: Some(data.to_vec()),
+        }
+        .finalize()
+    }
+
+    #[test]
+    fn round_trip() {
+        let http = Http {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            dst_addr: Some(Address::from(Ipv4Addr::new(198, 51, 100, 1))),
+            src_port: Some(40000),
+            dst_port: Some(80),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            handshake_rtt: Some(10_000),
+            max_time: Some(Timeval::new(60, 0)),
+            http_flags: None,
+            stop_reason: Some(HttpStopReason::Done),
+            http_type: Some(HttpType::HTTP),
+            host: Some(CString::new("www.example.org").unwrap()),
+            file: Some(CString::new("/").unwrap()),
+            buffer_count: None,
+            buffers: vec![
+                buffer(
+                    HttpBufferDirection::Transmitted,
+                    HttpBufferType::Header,
+                    b"GET / HTTP/1.1\r\nHost: www.example.org\r\n\r\n",
+                ),
+                buffer(
+                    HttpBufferDirection::Received,
+                    HttpBufferType::Header,
+                    b"HTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\n",
+                ),
+                buffer(
+                    HttpBufferDirection::Received,
+                    HttpBufferType::Header,
+                    b"Content-Length: 5\r\n\r\n",
+                ),
+                buffer(
+                    HttpBufferDirection::Received,
+                    HttpBufferType::Data,
+                    b"oops\n",
+                ),
+            ],
+        }
+        .finalize();
+        let bytes = Object::Http(http.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + http.length as usize);
+        let objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::Http(http.clone())]);
+
+        assert_eq!(
+            http.status(),
+            Some(HttpStatus {
+                version: "HTTP/1.1".to_string(),
+                code: 404,
+                reason: "Not Found".to_string()
+            })
+        );
+        assert_eq!(http.headers().len(), 2);
+        assert_eq!(http.header("content-length"), Some("5".to_string()));
+        assert_eq!(http.response_body(), b"oops\n");
+    }
+
+    #[test]
+    fn buffer_data_parameter() {
+        // Flags 1 (direction), 2 (type), 4 (length) and 5 (data), 7 bytes of parameters.
+        let input = [0x1b, 0x00, 0x07, 0x02, 0x02, 0x00, 0x03, 0x48, 0x54, 0x54];
+        let (rest, buffer) = HttpBuffer::read(input.view_bits(), deku::ctx::Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(buffer.direction, Some(HttpBufferDirection::Received));
+        assert_eq!(buffer.buffer_type, Some(HttpBufferType::Header));
+        assert_eq!(buffer.data, Some(b"HTT".to_vec()));
+        assert_eq!(buffer.clone().finalize(), buffer);
+    }
+}
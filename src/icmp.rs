@@ -0,0 +1,224 @@
+use crate::Address;
+
+/// Meaning of an ICMP or ICMPv6 type and code, independent of the address family.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IcmpKind {
+    EchoReply,
+    EchoRequest,
+    /// Time exceeded, code 0 when the TTL expired in transit and 1 when the fragment
+    /// reassembly time was exceeded.
+    TimeExceeded {
+        code: u8,
+    },
+    DestUnreach {
+        reason: UnreachableReason,
+    },
+    /// Packet too big (ICMPv6), or fragmentation needed and DF set (ICMPv4).
+    PacketTooBig,
+    ParamProblem,
+    TimestampReply,
+    /// Any other message.
+    Other {
+        icmp_type: u8,
+        icmp_code: u8,
+    },
+}
+
+/// Reason of a destination unreachable message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnreachableReason {
+    /// Network unreachable (ICMPv4), or no route to destination (ICMPv6).
+    Network,
+    /// Host unreachable (ICMPv4), or address unreachable (ICMPv6).
+    Host,
+    Protocol,
+    Port,
+    SourceRouteFailed,
+    AdministrativelyProhibited,
+    /// Beyond scope of source address (ICMPv6).
+    BeyondScope,
+    /// Any other code.
+    Other(u8),
+}
+
+impl IcmpKind {
+    /// Classifies an ICMP message, `ipv6` telling whether it is an ICMPv6 message.
+    /// ```
+    /// use warts::IcmpKind;
+    /// assert!(IcmpKind::new(11, 0, false).is_ttl_expired());
+    /// assert!(IcmpKind::new(3, 0, true).is_ttl_expired());
+    /// assert!(IcmpKind::new(3, 0, false).is_unreachable());
+    /// ```
+    pub fn new(icmp_type: u8, icmp_code: u8, ipv6: bool) -> Self {
+        if ipv6 {
+            Self::from_icmpv6(icmp_type, icmp_code)
+        } else {
+            Self::from_icmpv4(icmp_type, icmp_code)
+        }
+    }
+
+    /// Classifies an ICMP message received from `addr`, whose family tells whether it is
+    /// an ICMPv6 message; `None` if the type, code or IP address is missing.
+    pub(crate) fn from_reply(
+        icmp_type: Option<u8>,
+        icmp_code: Option<u8>,
+        addr: Option<&Address>,
+    ) -> Option<Self> {
+        let ipv6 = addr?.ip()?.is_ipv6();
+        Some(Self::new(icmp_type?, icmp_code?, ipv6))
+    }
+
+    fn from_icmpv4(icmp_type: u8, icmp_code: u8) -> Self {
+        match (icmp_type, icmp_code) {
+            (0, _) => IcmpKind::EchoReply,
+            (3, 4) => IcmpKind::PacketTooBig,
+            (3, code) => IcmpKind::DestUnreach {
+                reason: match code {
+                    0 => UnreachableReason::Network,
+                    1 => UnreachableReason::Host,
+                    2 => UnreachableReason::Protocol,
+                    3 => UnreachableReason::Port,
+                    5 => UnreachableReason::SourceRouteFailed,
+                    9 | 10 | 13 => UnreachableReason::AdministrativelyProhibited,
+                    code => UnreachableReason::Other(code),
+                },
+            },
+            (8, _) => IcmpKind::EchoRequest,
+            (11, code) => IcmpKind::TimeExceeded { code },
+            (12, _) => IcmpKind::ParamProblem,
+            (14, _) => IcmpKind::TimestampReply,
+            (icmp_type, icmp_code) => IcmpKind::Other {
+                icmp_type,
+                icmp_code,
+            },
+        }
+    }
+
+    fn from_icmpv6(icmp_type: u8, icmp_code: u8) -> Self {
+        match (icmp_type, icmp_code) {
+            (1, code) => IcmpKind::DestUnreach {
+                reason: match code {
+                    0 => UnreachableReason::Network,
+                    1 => UnreachableReason::AdministrativelyProhibited,
+                    2 => UnreachableReason::BeyondScope,
+                    3 => UnreachableReason::Host,
+                    4 => UnreachableReason::Port,
+                    code => UnreachableReason::Other(code),
+                },
+            },
+            (2, _) => IcmpKind::PacketTooBig,
+            (3, code) => IcmpKind::TimeExceeded { code },
+            (4, _) => IcmpKind::ParamProblem,
+            (128, _) => IcmpKind::EchoRequest,
+            (129, _) => IcmpKind::EchoReply,
+            (icmp_type, icmp_code) => IcmpKind::Other {
+                icmp_type,
+                icmp_code,
+            },
+        }
+    }
+
+    /// Returns true if the TTL (or hop limit) expired in transit.
+    pub fn is_ttl_expired(&self) -> bool {
+        matches!(self, IcmpKind::TimeExceeded { code: 0 })
+    }
+
+    /// Returns true for destination unreachable messages. ICMPv4 fragmentation needed
+    /// (type 3, code 4) is classified as [`IcmpKind::PacketTooBig`], like its ICMPv6
+    /// counterpart, and is therefore not reported here; see [`IcmpKind::is_packet_too_big`].
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, IcmpKind::DestUnreach { .. })
+    }
+
+    /// Returns true for port unreachable messages, the usual reply of a UDP traceroute
+    /// destination.
+    pub fn is_port_unreachable(&self) -> bool {
+        matches!(
+            self,
+            IcmpKind::DestUnreach {
+                reason: UnreachableReason::Port
+            }
+        )
+    }
+
+    pub fn is_echo_reply(&self) -> bool {
+        matches!(self, IcmpKind::EchoReply)
+    }
+
+    pub fn is_packet_too_big(&self) -> bool {
+        matches!(self, IcmpKind::PacketTooBig)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IcmpKind, Object, UnreachableReason};
+
+    #[test]
+    fn families() {
+        assert_eq!(
+            IcmpKind::new(11, 0, false),
+            IcmpKind::TimeExceeded { code: 0 }
+        );
+        assert_eq!(
+            IcmpKind::new(3, 0, true),
+            IcmpKind::TimeExceeded { code: 0 }
+        );
+        assert_eq!(
+            IcmpKind::new(3, 0, false),
+            IcmpKind::DestUnreach {
+                reason: UnreachableReason::Network
+            }
+        );
+        assert!(IcmpKind::new(3, 4, false).is_packet_too_big());
+        assert!(IcmpKind::new(2, 0, true).is_packet_too_big());
+        assert!(IcmpKind::new(1, 4, true).is_port_unreachable());
+        assert!(IcmpKind::new(129, 0, true).is_echo_reply());
+        assert!(!IcmpKind::new(11, 1, false).is_ttl_expired());
+        assert_eq!(
+            IcmpKind::new(5, 1, false),
+            IcmpKind::Other {
+                icmp_type: 5,
+                icmp_code: 1
+            }
+        );
+    }
+
+    #[test]
+    fn traceroute_hops() {
+        for data in [
+            &include_bytes!("../data/trace_google_dns_v4_default.warts")[..],
+            &include_bytes!("../data/trace_google_dns_v6_default.warts")[..],
+        ] {
+            for mut object in Object::all_from_bytes(data) {
+                object.dereference();
+                if let Object::Traceroute(trace) = object {
+                    let kinds: Vec<_> = trace
+                        .hops
+                        .iter()
+                        .map(|hop| hop.icmp_kind().unwrap())
+                        .collect();
+                    let (last, others) = kinds.split_last().unwrap();
+                    assert!(last.is_port_unreachable());
+                    assert!(others.iter().all(|kind| kind.is_ttl_expired()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ping_replies() {
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        for mut object in Object::all_from_bytes(data) {
+            object.dereference();
+            if let Object::Ping(ping) = object {
+                assert!(!ping.reply.is_empty());
+                for reply in &ping.reply {
+                    assert!(reply.icmp_kind().unwrap().is_echo_reply());
+                }
+            }
+        }
+    }
+}
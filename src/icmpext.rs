@@ -1,52 +1,319 @@
+use crate::WartsSized;
+use deku::bitvec::{BitSlice, BitVec, BitView, Msb0};
+use deku::ctx::{Endian, Limit};
 use deku::prelude::*;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
 
-/// ICMP extension (MPLS-only).
+/// ICMP extension object (RFC 4884), such as an MPLS label stack (RFC 4950) or
+/// interface information (RFC 5837).
 ///
-// TOOD
-// ```
-// use warts::{ICMPExtension, MPLSLabel};
-// let label = MPLSLabel{
-//     label: 1234,
-//     experimental: 0,
-//     bottom_of_stack: true,
-//     ttl: 8
-// };
-// let ext = ICMPExtension::new(vec![label]);
-// ```
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+/// ```
+/// use warts::{ICMPExtension, MPLSLabel};
+/// let label = MPLSLabel::new(1234, 0, true, 8);
+/// let ext = ICMPExtension::new(vec![label]);
+/// assert_eq!(ext.mpls_labels()[0].label(), 1234);
+/// assert!(ext.has_valid_mpls_stack());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct ICMPExtension {
     /// Length of data that follows.
-    #[deku(update = "self.mpls_labels.len() * 4")]
+    #[deku(update = "self.body.warts_size()")]
     data_length: u16,
     /// ICMP extension class number.
-    // #[deku(assert_eq = "1")]
     ext_class: u8,
     /// ICMP extension type number.
-    // #[deku(assert_eq = "1")]
     ext_type: u8,
-    /// ICMP extension data, if any.
-    #[deku(count = "data_length / 4")]
-    mpls_labels: Vec<MPLSLabel>,
+    /// ICMP extension data.
+    #[deku(ctx = "*ext_class, *ext_type, *data_length")]
+    body: ICMPExtensionBody,
+}
+
+/// Data of an ICMP extension object.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ICMPExtensionBody {
+    /// MPLS label stack (class 1, type 1).
+    Mpls(Vec<MPLSLabel>),
+    /// Interface information (class 2).
+    InterfaceInformation(InterfaceInformation),
+    /// Any other object, or an object that could not be decoded, as raw bytes.
+    Unknown(Vec<u8>),
+}
+
+/// Role of the interface described by an interface information object.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterfaceRole {
+    /// Interface on which the datagram that triggered the ICMP message arrived.
+    Incoming = 0,
+    /// Sub-IP component of the incoming interface.
+    SubIp = 1,
+    /// Interface through which the datagram would have been forwarded.
+    Outgoing = 2,
+    /// IP next hop to which the datagram would have been forwarded.
+    NextHop = 3,
+}
+
+/// Interface information object (RFC 5837).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterfaceInformation {
+    pub role: InterfaceRole,
+    /// ifIndex of the interface.
+    pub if_index: Option<u32>,
+    /// IP address of the interface.
+    pub address: Option<IpAddr>,
+    /// Name of the interface, at most 63 bytes long; longer names cannot be written.
+    pub name: Option<String>,
+    /// MTU of the interface.
+    pub mtu: Option<u32>,
 }
 
 impl ICMPExtension {
     pub fn new(mpls_labels: Vec<MPLSLabel>) -> Self {
+        Self::with_body(1, 1, ICMPExtensionBody::Mpls(mpls_labels))
+    }
+
+    pub fn from_interface_information(information: InterfaceInformation) -> Self {
+        let ext_type = information.c_type();
+        Self::with_body(
+            2,
+            ext_type,
+            ICMPExtensionBody::InterfaceInformation(information),
+        )
+    }
+
+    pub fn from_raw(ext_class: u8, ext_type: u8, data: Vec<u8>) -> Self {
+        Self::with_body(ext_class, ext_type, ICMPExtensionBody::Unknown(data))
+    }
+
+    fn with_body(ext_class: u8, ext_type: u8, body: ICMPExtensionBody) -> Self {
         ICMPExtension {
-            data_length: (mpls_labels.len() * 4) as u16,
-            ext_class: 1,
-            ext_type: 1,
-            mpls_labels,
+            data_length: body.warts_size() as u16,
+            ext_class,
+            ext_type,
+            body,
+        }
+    }
+
+    pub fn ext_class(&self) -> u8 {
+        self.ext_class
+    }
+
+    pub fn ext_type(&self) -> u8 {
+        self.ext_type
+    }
+
+    pub fn body(&self) -> &ICMPExtensionBody {
+        &self.body
+    }
+
+    /// Returns the MPLS label stack, or an empty slice if this is not an MPLS object.
+    pub fn mpls_labels(&self) -> &[MPLSLabel] {
+        match &self.body {
+            ICMPExtensionBody::Mpls(labels) => labels,
+            _ => &[],
+        }
+    }
+
+    /// Returns true if this is a non-empty MPLS label stack in which only the last entry
+    /// has the bottom-of-stack bit set.
+    pub fn has_valid_mpls_stack(&self) -> bool {
+        match &self.body {
+            ICMPExtensionBody::Mpls(labels) => MPLSLabel::is_valid_stack(labels),
+            _ => false,
+        }
+    }
+
+    /// Returns the interface information, if this is an interface information object.
+    pub fn interface_information(&self) -> Option<&InterfaceInformation> {
+        match &self.body {
+            ICMPExtensionBody::InterfaceInformation(information) => Some(information),
+            _ => None,
+        }
+    }
+}
+
+impl InterfaceInformation {
+    const IF_INDEX: u8 = 0x08;
+    const IP_ADDRESS: u8 = 0x04;
+    const NAME: u8 = 0x02;
+    const MTU: u8 = 0x01;
+
+    /// Returns the C-Type of the object, which encodes the role and the fields present.
+    pub fn c_type(&self) -> u8 {
+        let mut c_type = (self.role as u8) << 6;
+        if self.if_index.is_some() {
+            c_type |= Self::IF_INDEX;
+        }
+        if self.address.is_some() {
+            c_type |= Self::IP_ADDRESS;
+        }
+        if self.name.is_some() {
+            c_type |= Self::NAME;
+        }
+        if self.mtu.is_some() {
+            c_type |= Self::MTU;
+        }
+        c_type
+    }
+
+    fn parse(c_type: u8, data: &[u8]) -> Option<Self> {
+        let role = match c_type >> 6 {
+            0 => InterfaceRole::Incoming,
+            1 => InterfaceRole::SubIp,
+            2 => InterfaceRole::Outgoing,
+            _ => InterfaceRole::NextHop,
+        };
+        let mut rest = data;
+        let mut take = |n: usize| -> Option<&[u8]> {
+            if rest.len() < n {
+                return None;
+            }
+            let (value, after) = rest.split_at(n);
+            rest = after;
+            Some(value)
+        };
+        let mut information = InterfaceInformation {
+            role,
+            if_index: None,
+            address: None,
+            name: None,
+            mtu: None,
+        };
+        if c_type & Self::IF_INDEX != 0 {
+            information.if_index = Some(u32::from_be_bytes(take(4)?.try_into().ok()?));
+        }
+        if c_type & Self::IP_ADDRESS != 0 {
+            let afi = u16::from_be_bytes(take(4)?[..2].try_into().ok()?);
+            information.address = match afi {
+                1 => Some(IpAddr::from(<[u8; 4]>::try_from(take(4)?).ok()?)),
+                2 => Some(IpAddr::from(<[u8; 16]>::try_from(take(16)?).ok()?)),
+                _ => return None,
+            };
+        }
+        if c_type & Self::NAME != 0 {
+            let length = *take(1)?.first()? as usize;
+            let name = take(length.checked_sub(1)?)?;
+            let end = name.iter().rposition(|x| *x != 0).map_or(0, |i| i + 1);
+            information.name = Some(String::from_utf8(name[..end].to_vec()).ok()?);
+        }
+        if c_type & Self::MTU != 0 {
+            information.mtu = Some(u32::from_be_bytes(take(4)?.try_into().ok()?));
+        }
+        Some(information)
+    }
+
+    /// Maximum length of an interface name in bytes, as per RFC 5837.
+    const MAX_NAME_LENGTH: usize = 63;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, DekuError> {
+        let mut data = Vec::new();
+        if let Some(if_index) = self.if_index {
+            data.extend_from_slice(&if_index.to_be_bytes());
         }
+        match self.address {
+            Some(IpAddr::V4(address)) => {
+                data.extend_from_slice(&[0, 1, 0, 0]);
+                data.extend_from_slice(&address.octets());
+            }
+            Some(IpAddr::V6(address)) => {
+                data.extend_from_slice(&[0, 2, 0, 0]);
+                data.extend_from_slice(&address.octets());
+            }
+            None => {}
+        }
+        if let Some(name) = &self.name {
+            if name.len() > Self::MAX_NAME_LENGTH {
+                return Err(DekuError::InvalidParam(format!(
+                    "interface name of {} bytes exceeds {} bytes",
+                    name.len(),
+                    Self::MAX_NAME_LENGTH
+                )));
+            }
+            // The sub-object length includes the length octet and is a multiple of 4.
+            let length = (name.len() + 1).div_ceil(4) * 4;
+            data.push(length as u8);
+            data.extend_from_slice(name.as_bytes());
+            data.resize(data.len() + length - name.len() - 1, 0);
+        }
+        if let Some(mtu) = self.mtu {
+            data.extend_from_slice(&mtu.to_be_bytes());
+        }
+        Ok(data)
+    }
+}
+
+impl<'a> DekuRead<'a, (Endian, u8, u8, u16)> for ICMPExtensionBody {
+    fn read(
+        input: &'a BitSlice<u8, Msb0>,
+        (endian, ext_class, ext_type, data_length): (Endian, u8, u8, u16),
+    ) -> Result<(&'a BitSlice<u8, Msb0>, Self), DekuError> {
+        let (rest, data) =
+            Vec::<u8>::read(input, (Limit::new_count(data_length as usize), endian))?;
+        let body = match (ext_class, ext_type) {
+            (1, 1) if data.len() % 4 == 0 => {
+                let (_, labels) = Vec::<MPLSLabel>::read(
+                    data.view_bits(),
+                    (Limit::new_count(data.len() / 4), endian),
+                )?;
+                ICMPExtensionBody::Mpls(labels)
+            }
+            // Objects whose encoding we would not reproduce exactly (e.g. unusual name
+            // padding) are kept as raw bytes so that they round-trip.
+            (2, c_type) => match InterfaceInformation::parse(c_type, &data) {
+                Some(information) if information.to_bytes().as_ref() == Ok(&data) => {
+                    ICMPExtensionBody::InterfaceInformation(information)
+                }
+                _ => ICMPExtensionBody::Unknown(data),
+            },
+            _ => ICMPExtensionBody::Unknown(data),
+        };
+        Ok((rest, body))
     }
+}
+
+impl DekuWrite<(Endian, u8, u8, u16)> for ICMPExtensionBody {
+    fn write(
+        &self,
+        output: &mut BitVec<u8, Msb0>,
+        (endian, _, _, _): (Endian, u8, u8, u16),
+    ) -> Result<(), DekuError> {
+        match self {
+            ICMPExtensionBody::Mpls(labels) => labels.write(output, endian),
+            ICMPExtensionBody::InterfaceInformation(information) => {
+                information.to_bytes()?.write(output, endian)
+            }
+            ICMPExtensionBody::Unknown(data) => data.write(output, endian),
+        }
+    }
+}
 
-    pub fn mpls_labels(&self) -> &Vec<MPLSLabel> {
-        &self.mpls_labels
+impl WartsSized for ICMPExtension {
+    fn warts_size(&self) -> usize {
+        4 + self.body.warts_size()
+    }
+}
+
+impl WartsSized for ICMPExtensionBody {
+    fn warts_size(&self) -> usize {
+        match self {
+            ICMPExtensionBody::Mpls(labels) => 4 * labels.len(),
+            // An invalid object cannot be written; its size does not matter.
+            ICMPExtensionBody::InterfaceInformation(information) => {
+                information.to_bytes().map_or(0, |data| data.len())
+            }
+            ICMPExtensionBody::Unknown(data) => data.len(),
+        }
     }
 }
 
 /// An MPLS label stack entry.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MPLSLabel {
     // We do not systematically parse MPLS labels as this is relatively slow;
@@ -62,6 +329,124 @@ pub struct MPLSLabel {
 }
 
 impl MPLSLabel {
-    // TODO: Getters
-    // TODO: Impl From<MPLSLabel> for u32
+    pub fn new(label: u32, experimental: u8, bottom_of_stack: bool, ttl: u8) -> Self {
+        MPLSLabel {
+            data: (label & 0xfffff) << 12
+                | ((experimental & 0x07) as u32) << 9
+                | (bottom_of_stack as u32) << 8
+                | ttl as u32,
+        }
+    }
+
+    /// Label value (20 bits).
+    pub fn label(&self) -> u32 {
+        self.data >> 12
+    }
+
+    /// Traffic class, formerly experimental bits (3 bits).
+    pub fn experimental(&self) -> u8 {
+        ((self.data >> 9) & 0x07) as u8
+    }
+
+    /// Bottom-of-stack (S) bit.
+    pub fn bottom_of_stack(&self) -> bool {
+        (self.data >> 8) & 0x01 == 1
+    }
+
+    pub fn ttl(&self) -> u8 {
+        (self.data & 0xff) as u8
+    }
+
+    /// Returns true if the stack is non-empty and only its last entry has the
+    /// bottom-of-stack bit set.
+    pub fn is_valid_stack(labels: &[MPLSLabel]) -> bool {
+        match labels.split_last() {
+            Some((last, others)) => {
+                last.bottom_of_stack() && others.iter().all(|label| !label.bottom_of_stack())
+            }
+            None => false,
+        }
+    }
+}
+
+impl From<u32> for MPLSLabel {
+    fn from(data: u32) -> Self {
+        MPLSLabel { data }
+    }
+}
+
+impl From<MPLSLabel> for u32 {
+    fn from(label: MPLSLabel) -> Self {
+        label.data
+    }
+}
+
+impl Display for MPLSLabel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mpls ext ttl: {}, s: {}, exp: {}, label: {}",
+            self.ttl(),
+            self.bottom_of_stack() as u8,
+            self.experimental(),
+            self.label()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ICMPExtension, ICMPExtensionBody, InterfaceInformation, InterfaceRole, MPLSLabel};
+    use deku::bitvec::{BitVec, BitView};
+    use deku::ctx::Endian;
+    use deku::prelude::*;
+
+    #[test]
+    fn interface_name_length() {
+        let mut information = InterfaceInformation {
+            role: InterfaceRole::Incoming,
+            if_index: None,
+            address: None,
+            name: Some("x".repeat(63)),
+            mtu: None,
+        };
+        // A 63-byte name fills the 64-byte sub-object, including the length octet.
+        let extension = ICMPExtension::from_interface_information(information.clone());
+        assert_eq!(extension.data_length, 64);
+        let mut output = BitVec::new();
+        extension.write(&mut output, Endian::Big).unwrap();
+        let bytes = output.into_vec();
+        assert_eq!(bytes[4], 64);
+        let (_, parsed) = ICMPExtension::read(bytes.view_bits(), Endian::Big).unwrap();
+        assert_eq!(parsed, extension);
+
+        information.name = Some("x".repeat(300));
+        let extension = ICMPExtension::from_interface_information(information);
+        assert!(extension.write(&mut BitVec::new(), Endian::Big).is_err());
+
+        // Longer names found in a file are kept as raw bytes.
+        let mut data = vec![0x00, 0x44, 0x02, InterfaceInformation::NAME, 0x44];
+        data.extend_from_slice(&[b'x'; 67]);
+        let (_, parsed) = ICMPExtension::read(data.view_bits(), Endian::Big).unwrap();
+        assert!(matches!(parsed.body(), ICMPExtensionBody::Unknown(_)));
+    }
+
+    #[test]
+    fn mpls_label() {
+        let label = MPLSLabel::new(24015, 5, true, 254);
+        assert_eq!(u32::from(label), 0x05dcfbfe);
+        assert_eq!(MPLSLabel::from(0x05dcfbfe), label);
+        assert_eq!((label.label(), label.experimental()), (24015, 5));
+        assert!(label.bottom_of_stack());
+        assert_eq!(label.ttl(), 254);
+        assert_eq!(
+            label.to_string(),
+            "mpls ext ttl: 254, s: 1, exp: 5, label: 24015"
+        );
+        let top = MPLSLabel::new(16, 0, false, 1);
+        assert!(MPLSLabel::is_valid_stack(&[top, label]));
+        assert!(!MPLSLabel::is_valid_stack(&[label, top]));
+        assert!(!MPLSLabel::is_valid_stack(&[label, label]));
+        assert!(!MPLSLabel::is_valid_stack(&[]));
+    }
 }
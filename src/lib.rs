@@ -9,29 +9,57 @@ mod macros;
 
 mod address;
 mod address_deprecated;
+mod context;
 mod cycle;
+mod dealias;
 mod flags;
+mod host;
+mod http;
+mod icmp;
 mod icmpext;
 mod list;
+mod measurement;
+mod neighbourdisc;
 mod object;
+mod packet;
 mod ping;
+#[cfg(feature = "serde")]
+mod serde_helpers;
 mod sized;
+mod sniff;
+mod sting;
+mod stop;
+mod tbit;
 mod timeval;
 mod trace;
 mod tracelb;
+mod udpprobe;
 
 pub use address::*;
 pub use address_deprecated::*;
+pub use context::*;
 pub use cycle::*;
+pub use dealias::*;
 pub use flags::*;
+pub use host::*;
+pub use http::*;
+pub use icmp::*;
 pub use icmpext::*;
 pub use list::*;
+pub use measurement::*;
+pub use neighbourdisc::*;
 pub use object::*;
+pub use packet::*;
 pub use ping::*;
 pub use sized::*;
+pub use sniff::*;
+pub use sting::*;
+pub use stop::*;
+pub use tbit::*;
 pub use timeval::*;
 pub use trace::*;
 pub use tracelb::*;
+pub use udpprobe::*;
 
 // This trait must be imported by consumers who wants to write warts objects.
 pub use deku::DekuContainerWrite;
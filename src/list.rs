@@ -3,26 +3,39 @@ use deku::prelude::*;
 use std::ffi::CString;
 
 /// A list of warts objects.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct List {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
     /// List ID assigned by warts from a counter.
     pub list_id: u32,
     /// List ID assigned by a person.
     pub list_id_human: u32,
     /// List Name assigned by a person.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::cstring"))]
     pub name: CString,
     /// Flags.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     /// Parameter length (optional, included if any flags are set).
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// Description, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
     pub description: Option<CString>,
     /// Monitor name, included if flag 2 is set.
     #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
     pub monitor_name: Option<CString>,
 }
 
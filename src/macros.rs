@@ -6,3 +6,61 @@ macro_rules! push_flag {
         $b += $d.warts_size();
     };
 }
+
+macro_rules! impl_measurement {
+    ($t:ty) => {
+        impl $crate::Measurement for $t {
+            fn list_id(&self) -> Option<u32> {
+                self.list_id
+            }
+            fn cycle_id(&self) -> Option<u32> {
+                self.cycle_id
+            }
+            fn user_id(&self) -> Option<u32> {
+                self.user_id
+            }
+            fn start_timeval(&self) -> Option<&$crate::Timeval> {
+                self.start_time.as_ref()
+            }
+            fn src_address(&self) -> Option<&$crate::Address> {
+                self.src_addr.as_ref()
+            }
+            fn dst_address(&self) -> Option<&$crate::Address> {
+                self.dst_addr.as_ref()
+            }
+        }
+    };
+}
+
+/// Implements the deku traits and [`WartsSized`](crate::WartsSized) for a bitflags type
+/// stored as its underlying integer. Unknown bits are retained.
+macro_rules! impl_deku_bitflags {
+    ($t:ty, $bits:ty) => {
+        impl deku::DekuRead<'_, deku::ctx::Endian> for $t {
+            fn read(
+                input: &'_ deku::bitvec::BitSlice<u8, deku::bitvec::Msb0>,
+                endian: deku::ctx::Endian,
+            ) -> Result<(&'_ deku::bitvec::BitSlice<u8, deku::bitvec::Msb0>, Self), deku::DekuError>
+            {
+                let (rest, bits) = <$bits>::read(input, endian)?;
+                Ok((rest, Self::from_bits_retain(bits)))
+            }
+        }
+
+        impl deku::DekuWrite<deku::ctx::Endian> for $t {
+            fn write(
+                &self,
+                output: &mut deku::bitvec::BitVec<u8, deku::bitvec::Msb0>,
+                endian: deku::ctx::Endian,
+            ) -> Result<(), deku::DekuError> {
+                self.bits().write(output, endian)
+            }
+        }
+
+        impl $crate::WartsSized for $t {
+            fn warts_size(&self) -> usize {
+                std::mem::size_of::<$bits>()
+            }
+        }
+    };
+}
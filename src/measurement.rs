@@ -0,0 +1,95 @@
+use crate::{Address, MultipathTraceroute, Ping, Timeval, Traceroute};
+use std::net::IpAddr;
+
+/// Accessors common to all measurement objects (traceroutes, pings, ...).
+///
+/// Addresses are returned as [`IpAddr`] only once they have been resolved,
+/// see [`Object::dereference`](crate::Object::dereference).
+/// ```
+/// use warts::{Measurement, Object};
+/// let data = include_bytes!("../data/trace_google_dns_v4_default.warts");
+/// for mut object in Object::all_from_bytes(data) {
+///     object.dereference();
+///     if let Some(m) = object.measurement() {
+///         println!("{:?} -> {:?}", m.src_addr(), m.dst_addr());
+///     }
+/// }
+/// ```
+pub trait Measurement {
+    /// List ID assigned by warts, if any.
+    fn list_id(&self) -> Option<u32>;
+    /// Cycle ID assigned by warts, if any.
+    fn cycle_id(&self) -> Option<u32>;
+    /// User ID assigned to the measurement, if any.
+    fn user_id(&self) -> Option<u32>;
+    /// Time at which the measurement commenced, as stored in the warts file.
+    fn start_timeval(&self) -> Option<&Timeval>;
+    /// Source address, as stored in the warts file.
+    fn src_address(&self) -> Option<&Address>;
+    /// Destination address, as stored in the warts file.
+    fn dst_address(&self) -> Option<&Address>;
+
+    /// Time at which the measurement commenced, as a `chrono` date-time.
+    #[cfg(feature = "chrono")]
+    fn start_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.start_timeval().map(|&tv| tv.into())
+    }
+
+    /// Time at which the measurement commenced, as a `time` date-time.
+    #[cfg(feature = "time")]
+    fn start_offset_date_time(&self) -> Option<time::OffsetDateTime> {
+        self.start_timeval().map(|&tv| tv.into())
+    }
+
+    /// Source IP address, if present and resolved.
+    fn src_addr(&self) -> Option<IpAddr> {
+        self.src_address().and_then(Address::ip)
+    }
+
+    /// Destination IP address, if present and resolved.
+    fn dst_addr(&self) -> Option<IpAddr> {
+        self.dst_address().and_then(Address::ip)
+    }
+}
+
+impl_measurement!(Traceroute);
+impl_measurement!(Ping);
+impl_measurement!(MultipathTraceroute);
+
+#[cfg(test)]
+mod tests {
+    use crate::Object;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn traceroute_accessors() {
+        let data = include_bytes!("../data/trace_google_dns_v4_default.warts");
+        let mut objects = Object::all_from_bytes(data);
+        let object = &mut objects[2];
+        object.dereference();
+        let m = object.measurement().unwrap();
+        assert_eq!(m.list_id(), Some(1));
+        assert_eq!(m.cycle_id(), Some(1));
+        assert_eq!(
+            m.src_addr(),
+            Some(IpAddr::V4(Ipv4Addr::new(137, 194, 165, 109)))
+        );
+        assert_eq!(m.dst_addr(), Some(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8))));
+        #[cfg(feature = "chrono")]
+        assert_eq!(m.start_time().unwrap().timestamp(), 1644327319);
+        #[cfg(feature = "time")]
+        assert_eq!(
+            m.start_offset_date_time().unwrap().unix_timestamp(),
+            1644327319
+        );
+    }
+
+    #[test]
+    fn non_measurement_objects() {
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        let objects = Object::all_from_bytes(data);
+        assert!(objects[0].measurement().is_none());
+        assert!(objects[1].measurement().is_none());
+        assert!(objects[2].measurement().is_some());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::object::AddressSlot;
+use crate::{Address, Flags, Measurement, Timeval, WartsSized};
+use deku::prelude::*;
+use std::ffi::CString;
+
+/// Neighbour discovery method.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum NeighbourDiscoveryMethod {
+    /// ARP requests (IPv4).
+    #[deku(id = "0x01")]
+    ARP,
+    /// Neighbour solicitations (IPv6).
+    #[deku(id = "0x02")]
+    NDNeighbourSolicitation,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// A neighbour discovery, used to find the link-layer address of an IP address.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct NeighbourDiscovery {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// List ID assigned by warts, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// Cycle ID assigned by warts, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// User ID assigned to the neighbour discovery, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Name of the interface used, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub interface_name: Option<CString>,
+    /// Time neighbour discovery commenced, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub start_time: Option<Timeval>,
+    /// Neighbour discovery flags, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub nd_flags: Option<u8>,
+    /// Number of attempts, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub attempts: Option<u16>,
+    /// Time to wait between probes in milliseconds, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub wait: Option<u16>,
+    /// Number of replies wanted, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub reply_count: Option<u16>,
+    /// Number of probes sent, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub probe_count: Option<u16>,
+    /// Neighbour discovery method, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub method: Option<NeighbourDiscoveryMethod>,
+    /// Source IP address used in probes, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub src_ip: Option<Address>,
+    /// Source MAC address used in probes, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)")]
+    pub src_mac: Option<Address>,
+    /// IP address whose MAC address is searched, included if flag 14 is set.
+    #[deku(cond = "flags.get(14)")]
+    pub dst_ip: Option<Address>,
+    /// MAC address found, included if flag 15 is set.
+    #[deku(cond = "flags.get(15)")]
+    pub dst_mac: Option<Address>,
+    /// Probes sent.
+    #[deku(count = "probe_count.unwrap_or(0)")]
+    pub probes: Vec<NeighbourDiscoveryProbe>,
+}
+
+/// A neighbour discovery probe and its replies.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct NeighbourDiscoveryProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Transmit time, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub tx: Option<Timeval>,
+    /// Number of replies, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub reply_count: Option<u16>,
+    /// Replies received, if any.
+    #[deku(count = "reply_count.unwrap_or(0)")]
+    pub replies: Vec<NeighbourDiscoveryReply>,
+}
+
+/// A reply to a neighbour discovery probe.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct NeighbourDiscoveryReply {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Receive time, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub rx: Option<Timeval>,
+    /// Source IP address of the reply, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub src_ip: Option<Address>,
+    /// Source MAC address of the reply, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub src_mac: Option<Address>,
+}
+
+impl NeighbourDiscovery {
+    /// IP and MAC address fields of the neighbour discovery and of its replies.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        let mut slots = Vec::new();
+        for address in [
+            &mut self.src_ip,
+            &mut self.src_mac,
+            &mut self.dst_ip,
+            &mut self.dst_mac,
+        ] {
+            slots.push(AddressSlot::Optional(address, None));
+        }
+        for reply in self
+            .probes
+            .iter_mut()
+            .flat_map(|probe| probe.replies.iter_mut())
+        {
+            slots.push(AddressSlot::Optional(&mut reply.src_ip, None));
+            slots.push(AddressSlot::Optional(&mut reply.src_mac, None));
+        }
+        slots
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.probe_count = Some(self.probes.len() as u16);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.interface_name);
+        push_flag!(flags, param_length, 5, self.start_time);
+        push_flag!(flags, param_length, 6, self.nd_flags);
+        push_flag!(flags, param_length, 7, self.attempts);
+        push_flag!(flags, param_length, 8, self.wait);
+        push_flag!(flags, param_length, 9, self.reply_count);
+        push_flag!(flags, param_length, 10, self.probe_count);
+        push_flag!(flags, param_length, 11, self.method);
+        push_flag!(flags, param_length, 12, self.src_ip);
+        push_flag!(flags, param_length, 13, self.src_mac);
+        push_flag!(flags, param_length, 14, self.dst_ip);
+        push_flag!(flags, param_length, 15, self.dst_mac);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        let probes_size: usize = self.probes.iter().map(|probe| probe.warts_size()).sum();
+        self.length =
+            (self.flags.warts_size() + self.param_length.warts_size() + param_length + probes_size)
+                as u32;
+        self
+    }
+}
+
+impl Measurement for NeighbourDiscovery {
+    fn list_id(&self) -> Option<u32> {
+        self.list_id
+    }
+    fn cycle_id(&self) -> Option<u32> {
+        self.cycle_id
+    }
+    fn user_id(&self) -> Option<u32> {
+        self.user_id
+    }
+    fn start_timeval(&self) -> Option<&Timeval> {
+        self.start_time.as_ref()
+    }
+    fn src_address(&self) -> Option<&Address> {
+        self.src_ip.as_ref()
+    }
+    fn dst_address(&self) -> Option<&Address> {
+        self.dst_ip.as_ref()
+    }
+}
+
+impl NeighbourDiscoveryProbe {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.reply_count = Some(self.replies.len() as u16);
+        push_flag!(flags, param_length, 1, self.tx);
+        push_flag!(flags, param_length, 2, self.reply_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl NeighbourDiscoveryReply {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.rx);
+        push_flag!(flags, param_length, 2, self.src_ip);
+        push_flag!(flags, param_length, 3, self.src_mac);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for NeighbourDiscoveryMethod {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for NeighbourDiscoveryProbe {
+    fn warts_size(&self) -> usize {
+        let replies_size: usize = self.replies.iter().map(|reply| reply.warts_size()).sum();
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+            + replies_size
+    }
+}
+
+impl WartsSized for NeighbourDiscoveryReply {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, NeighbourDiscovery, NeighbourDiscoveryMethod, NeighbourDiscoveryProbe,
+        NeighbourDiscoveryReply, Object, Timeval,
+    };
+    use deku::DekuContainerWrite;
+    use std::ffi::CString;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn arp_round_trip() {
+        let mac = Address::from([0x00, 0x16, 0x3e, 0x01, 0x02, 0x03]);
+        // The reply addresses refer to the destination IP and MAC addresses.
+        let reply = NeighbourDiscoveryReply {
+            flags: Default::default(),
+            param_length: None,
+            rx: Some(Timeval::new(1644339038, 1500)),
+            src_ip: Some(Address::Reference(2)),
+            src_mac: Some(Address::Reference(3)),
+        }
+        .finalize();
+        let probe = NeighbourDiscoveryProbe {
+            flags: Default::default(),
+            param_length: None,
+            tx: Some(Timeval::new(1644339038, 1000)),
+            reply_count: None,
+            replies: vec![reply],
+        }
+        .finalize();
+        let nd = NeighbourDiscovery {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            interface_name: Some(CString::new("eth0").unwrap()),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            nd_flags: None,
+            attempts: Some(1),
+            wait: Some(1000),
+            reply_count: Some(1),
+            probe_count: None,
+            method: Some(NeighbourDiscoveryMethod::ARP),
+            src_ip: Some(Address::from(Ipv4Addr::new(192, 0, 2, 100))),
+            src_mac: Some(Address::from([0x00, 0x16, 0x3e, 0xaa, 0xbb, 0xcc])),
+            dst_ip: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            dst_mac: Some(mac),
+            probes: vec![probe],
+        }
+        .finalize();
+        let bytes = Object::NeighbourDiscovery(nd.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + nd.length as usize);
+        let mut objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::NeighbourDiscovery(nd)]);
+
+        objects[0].dereference();
+        let Object::NeighbourDiscovery(nd) = &objects[0] else {
+            unreachable!()
+        };
+        let reply = &nd.probes[0].replies[0];
+        assert_eq!(
+            reply.src_ip,
+            Some(Address::from(Ipv4Addr::new(192, 0, 2, 1)))
+        );
+        assert_eq!(reply.src_mac, Some(mac));
+    }
+}
@@ -1,36 +1,73 @@
-use crate::{
-    Address, AddressDeprecated, CycleStart, CycleStop, List, MultipathTraceroute, Ping, Traceroute,
-};
+use crate::{Address, Measurement};
 use deku::prelude::*;
 
-/// A warts object.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
-#[deku(endian = "big", magic = b"\x12\x05", type = "u16")]
-pub enum Object {
-    #[deku(id = "0x0001")]
-    List(List),
-    /// A start record denotes the starting point for a new cycle.
-    #[deku(id = "0x0002")]
-    CycleStart(CycleStart),
-    /// A definition record declares a cycle record whose corresponding start record is in a different file.
-    #[deku(id = "0x0003")]
-    CycleDefinition(CycleStart),
-    /// A cycle stop record denotes the end point for a cycle.
-    #[deku(id = "0x0004")]
-    CycleStop(CycleStop),
-    /// A network address (deprecated).
-    #[deku(id = "0x0005")]
-    Address(AddressDeprecated),
-    /// Traceroute structures consist of traceroute parameters, hop records, and an optional series
-    /// of additional data types for special types of traceroute invocation.
-    #[deku(id = "0x0006")]
-    Traceroute(Traceroute),
-    /// Ping structures consist of ping parameters and responses.
-    #[deku(id = "0x0007")]
-    Ping(Ping),
-    /// MDA traceroute
-    #[deku(id = "0x0008")]
-    MultipathTraceroute(MultipathTraceroute),
+pub use container::Object;
+
+mod container {
+    // The container code generated by `DekuRead` for `Object` triggers this lint, which
+    // cannot be allowed on the derive itself.
+    #![allow(clippy::manual_div_ceil)]
+
+    use crate::{
+        AddressDeprecated, CycleStart, CycleStop, Dealias, Host, Http, List, MultipathTraceroute,
+        NeighbourDiscovery, Ping, Sniff, Sting, Tbit, Traceroute, UdpProbe,
+    };
+    use deku::prelude::*;
+
+    /// A warts object.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[deku(endian = "big", magic = b"\x12\x05", type = "u16")]
+    pub enum Object {
+        #[deku(id = "0x0001")]
+        List(List),
+        /// A start record denotes the starting point for a new cycle.
+        #[deku(id = "0x0002")]
+        CycleStart(CycleStart),
+        /// A definition record declares a cycle record whose corresponding start record is in a different file.
+        #[deku(id = "0x0003")]
+        CycleDefinition(CycleStart),
+        /// A cycle stop record denotes the end point for a cycle.
+        #[deku(id = "0x0004")]
+        CycleStop(CycleStop),
+        /// A network address (deprecated).
+        #[deku(id = "0x0005")]
+        Address(AddressDeprecated),
+        /// Traceroute structures consist of traceroute parameters, hop records, and an optional series
+        /// of additional data types for special types of traceroute invocation.
+        #[deku(id = "0x0006")]
+        Traceroute(Traceroute),
+        /// Ping structures consist of ping parameters and responses.
+        #[deku(id = "0x0007")]
+        Ping(Ping),
+        /// MDA traceroute
+        #[deku(id = "0x0008")]
+        MultipathTraceroute(MultipathTraceroute),
+        /// Alias resolution
+        #[deku(id = "0x0009")]
+        Dealias(Dealias),
+        /// Neighbour discovery
+        #[deku(id = "0x000a")]
+        NeighbourDiscovery(NeighbourDiscovery),
+        /// TCP behaviour inference test
+        #[deku(id = "0x000b")]
+        Tbit(Tbit),
+        /// Sting
+        #[deku(id = "0x000c")]
+        Sting(Sting),
+        /// Sniff
+        #[deku(id = "0x000d")]
+        Sniff(Sniff),
+        /// DNS measurement
+        #[deku(id = "0x000e")]
+        Host(Host),
+        /// HTTP measurement
+        #[deku(id = "0x000f")]
+        Http(Http),
+        /// UDP probe
+        #[deku(id = "0x0010")]
+        UdpProbe(UdpProbe),
+    }
 }
 
 impl Object {
@@ -48,6 +85,24 @@ impl Object {
         objects
     }
 
+    /// Returns the object as a [`Measurement`], or `None` for lists, cycles and addresses.
+    pub fn measurement(&self) -> Option<&dyn Measurement> {
+        match self {
+            Object::Traceroute(t) => Some(t),
+            Object::Ping(p) => Some(p),
+            Object::MultipathTraceroute(t) => Some(t),
+            Object::Dealias(d) => Some(d),
+            Object::NeighbourDiscovery(nd) => Some(nd),
+            Object::Tbit(t) => Some(t),
+            Object::Sting(s) => Some(s),
+            Object::Sniff(s) => Some(s),
+            Object::Host(h) => Some(h),
+            Object::Http(h) => Some(h),
+            Object::UdpProbe(u) => Some(u),
+            _ => None,
+        }
+    }
+
     pub fn dereference(&mut self) {
         let mut table = Vec::new();
         match self {
@@ -58,7 +113,12 @@ impl Object {
                 if let Some(addr) = t.dst_addr {
                     table.push(addr);
                 }
-                for hop in t.hops.iter_mut() {
+                for hop in t
+                    .hops
+                    .iter_mut()
+                    .chain(t.pmtud.iter_mut().flat_map(|p| p.hops.iter_mut()))
+                    .chain(t.last_ditch.iter_mut().flat_map(|l| l.hops.iter_mut()))
+                {
                     match hop.addr {
                         Some(Address::Reference(_)) => { /* skip references */ }
                         Some(address) => {
@@ -67,8 +127,24 @@ impl Object {
                         _ => {}
                     }
                 }
+                if let Some(dtree) = &t.dtree {
+                    for address in [dtree.lss_stop, dtree.gss_stop].into_iter().flatten() {
+                        if !matches!(address, Address::Reference(_)) {
+                            table.push(address);
+                        }
+                    }
+                }
             }
-            Object::MultipathTraceroute(_) => todo!(),
+            Object::Ping(p) => push_addresses(&mut table, p.address_slots()),
+            Object::MultipathTraceroute(t) => push_addresses(&mut table, t.address_slots()),
+            Object::Dealias(d) => push_addresses(&mut table, d.address_slots()),
+            Object::NeighbourDiscovery(nd) => push_addresses(&mut table, nd.address_slots()),
+            Object::Tbit(t) => push_addresses(&mut table, t.address_slots()),
+            Object::Sting(st) => push_addresses(&mut table, st.address_slots()),
+            Object::Sniff(sn) => push_addresses(&mut table, sn.address_slots()),
+            Object::Host(h) => push_addresses(&mut table, h.address_slots()),
+            Object::Http(h) => push_addresses(&mut table, h.address_slots()),
+            Object::UdpProbe(u) => push_addresses(&mut table, u.address_slots()),
             _ => {}
         }
         self.dereference_with_table(&table);
@@ -83,16 +159,114 @@ impl Object {
                 if let Some(id) = t.dst_addr_id {
                     t.dst_addr = Some(table[id as usize - 1])
                 }
-                for mut hop in t.hops.iter_mut() {
+                for hop in t
+                    .hops
+                    .iter_mut()
+                    .chain(t.pmtud.iter_mut().flat_map(|p| p.hops.iter_mut()))
+                    .chain(t.last_ditch.iter_mut().flat_map(|l| l.hops.iter_mut()))
+                {
                     if let Some(Address::Reference(id)) = hop.addr {
                         hop.addr = Some(table[id as usize]);
                     } else if let Some(id) = hop.addr_id {
                         hop.addr = Some(table[id as usize - 1])
                     }
                 }
+                if let Some(dtree) = t.dtree.as_mut() {
+                    if let Some(Address::Reference(id)) = dtree.lss_stop {
+                        dtree.lss_stop = Some(table[id as usize]);
+                    } else if let Some(id) = dtree.lss_stop_addr_id {
+                        dtree.lss_stop = Some(table[id as usize - 1]);
+                    }
+                    if let Some(Address::Reference(id)) = dtree.gss_stop {
+                        dtree.gss_stop = Some(table[id as usize]);
+                    } else if let Some(id) = dtree.gss_stop_addr_id {
+                        dtree.gss_stop = Some(table[id as usize - 1]);
+                    }
+                }
             }
-            Object::MultipathTraceroute(_) => todo!(),
+            Object::Ping(p) => resolve_addresses(table, p.address_slots()),
+            Object::MultipathTraceroute(t) => resolve_addresses(table, t.address_slots()),
+            Object::Dealias(d) => resolve_addresses(table, d.address_slots()),
+            Object::NeighbourDiscovery(nd) => resolve_addresses(table, nd.address_slots()),
+            Object::Tbit(t) => resolve_addresses(table, t.address_slots()),
+            Object::Sting(st) => resolve_addresses(table, st.address_slots()),
+            Object::Sniff(sn) => resolve_addresses(table, sn.address_slots()),
+            Object::Host(h) => resolve_addresses(table, h.address_slots()),
+            Object::Http(h) => resolve_addresses(table, h.address_slots()),
+            Object::UdpProbe(u) => resolve_addresses(table, u.address_slots()),
             _ => {}
         }
     }
 }
+
+/// An address field of a measurement, in the order in which it appears on the wire.
+pub(crate) enum AddressSlot<'a> {
+    /// An optional address, along with the ID of the address in the file address table
+    /// (written by older versions of scamper instead of the address itself), if any.
+    Optional(&'a mut Option<Address>, Option<u32>),
+    /// An address which is always present.
+    Required(&'a mut Address),
+}
+
+/// Appends the addresses that are not references to `table`.
+fn push_addresses(table: &mut Vec<Address>, slots: Vec<AddressSlot>) {
+    for slot in slots {
+        match slot {
+            AddressSlot::Optional(Some(address), _) | AddressSlot::Required(address) => {
+                if !matches!(address, Address::Reference(_)) {
+                    table.push(*address);
+                }
+            }
+            AddressSlot::Optional(None, _) => {}
+        }
+    }
+}
+
+/// Replaces references and address IDs by the addresses of `table`.
+fn resolve_addresses(table: &[Address], slots: Vec<AddressSlot>) {
+    for slot in slots {
+        match slot {
+            AddressSlot::Optional(address, id) => {
+                if let Some(Address::Reference(id)) = address {
+                    *address = Some(table[*id as usize]);
+                } else if let Some(id) = id {
+                    *address = Some(table[id as usize - 1]);
+                }
+            }
+            AddressSlot::Required(address) => {
+                if let Address::Reference(id) = address {
+                    *address = table[*id as usize];
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Object;
+    use std::collections::HashSet;
+
+    #[test]
+    fn clone_and_hash() {
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        let objects = Object::all_from_bytes(data);
+        let mut set: HashSet<Object> = objects.iter().cloned().collect();
+        assert_eq!(set.len(), objects.len());
+        set.extend(objects.iter().cloned());
+        assert_eq!(set.len(), objects.len());
+    }
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let data = include_bytes!("../data/tracelb_google_dns_v4_default.warts");
+        for object in Object::all_from_bytes(data) {
+            let json = serde_json::to_string(&object).unwrap();
+            let other: Object = serde_json::from_str(&json).unwrap();
+            #[cfg(not(feature = "serde-skip-wire"))]
+            assert_eq!(other, object);
+            #[cfg(feature = "serde-skip-wire")]
+            assert_eq!(serde_json::to_string(&other).unwrap(), json);
+        }
+    }
+}
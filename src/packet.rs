@@ -0,0 +1,258 @@
+//! Decoding of the raw packets recorded by some measurements (e.g. tbit and sniff).
+
+use crate::WartsSized;
+use deku::prelude::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// IP protocol number of ICMP.
+pub const IPPROTO_ICMP: u8 = 1;
+/// IP protocol number of TCP.
+pub const IPPROTO_TCP: u8 = 6;
+/// IP protocol number of ICMPv6.
+pub const IPPROTO_ICMPV6: u8 = 58;
+
+/// Transport protocol carried by an IP packet, as recorded in the protocol (IPv4) or next
+/// header (IPv6) field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum IpProtocol {
+    /// Internet Control Message Protocol.
+    #[deku(id = "0x01")]
+    ICMP,
+    /// Transmission Control Protocol.
+    #[deku(id = "0x06")]
+    TCP,
+    /// User Datagram Protocol.
+    #[deku(id = "0x11")]
+    UDP,
+    /// Internet Control Message Protocol for IPv6.
+    #[deku(id = "0x3a")]
+    ICMPv6,
+    /// Protocol not known to this crate.
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+impl WartsSized for IpProtocol {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+/// The header of an IPv4 or IPv6 packet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IpHeader {
+    /// IP version, 4 or 6.
+    pub version: u8,
+    /// Type of service (IPv4) or traffic class (IPv6).
+    pub tos: u8,
+    /// Total length of the packet, including the header.
+    pub total_length: u16,
+    /// IP identifier, for IPv4 packets only.
+    pub ip_id: Option<u16>,
+    /// Time to live (IPv4) or hop limit (IPv6).
+    pub ttl: u8,
+    /// Protocol (IPv4) or next header after the extension headers (IPv6).
+    pub protocol: u8,
+    /// Source address.
+    pub src_addr: IpAddr,
+    /// Destination address.
+    pub dst_addr: IpAddr,
+    /// Length of the header, including IPv4 options and IPv6 extension headers.
+    pub header_length: usize,
+}
+
+impl IpHeader {
+    /// Decodes the IP header at the start of `data`, returns `None` if it is truncated
+    /// or if the IP version is unknown.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        match data.first()? >> 4 {
+            4 => Self::parse_v4(data),
+            6 => Self::parse_v6(data),
+            _ => None,
+        }
+    }
+
+    fn parse_v4(data: &[u8]) -> Option<Self> {
+        let header_length = ((data[0] & 0x0f) as usize) * 4;
+        if header_length < 20 || data.len() < header_length {
+            return None;
+        }
+        Some(IpHeader {
+            version: 4,
+            tos: data[1],
+            total_length: u16::from_be_bytes([data[2], data[3]]),
+            ip_id: Some(u16::from_be_bytes([data[4], data[5]])),
+            ttl: data[8],
+            protocol: data[9],
+            src_addr: IpAddr::V4(Ipv4Addr::new(data[12], data[13], data[14], data[15])),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(data[16], data[17], data[18], data[19])),
+            header_length,
+        })
+    }
+
+    fn parse_v6(data: &[u8]) -> Option<Self> {
+        if data.len() < 40 {
+            return None;
+        }
+        let mut src = [0u8; 16];
+        let mut dst = [0u8; 16];
+        src.copy_from_slice(&data[8..24]);
+        dst.copy_from_slice(&data[24..40]);
+        // Skip the extension headers to find the upper-layer protocol.
+        let mut protocol = data[6];
+        let mut header_length = 40;
+        loop {
+            match protocol {
+                // Hop-by-hop options, routing, destination options.
+                0 | 43 | 60 => {
+                    let ext = data.get(header_length..header_length + 2)?;
+                    protocol = ext[0];
+                    header_length += (ext[1] as usize + 1) * 8;
+                }
+                // Fragment.
+                44 => {
+                    protocol = *data.get(header_length)?;
+                    header_length += 8;
+                }
+                _ => break,
+            }
+        }
+        if data.len() < header_length {
+            return None;
+        }
+        Some(IpHeader {
+            version: 6,
+            tos: (data[0] << 4) | (data[1] >> 4),
+            total_length: u16::from_be_bytes([data[4], data[5]]).saturating_add(40),
+            ip_id: None,
+            ttl: data[7],
+            protocol,
+            src_addr: IpAddr::V6(Ipv6Addr::from(src)),
+            dst_addr: IpAddr::V6(Ipv6Addr::from(dst)),
+            header_length,
+        })
+    }
+}
+
+/// The header of a TCP segment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TcpHeader {
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub seq: u32,
+    pub ack: u32,
+    /// Length of the header, including the options.
+    pub header_length: usize,
+    /// TCP flags (FIN, SYN, RST, PSH, ACK, URG, ECE, CWR from the lowest bit).
+    pub flags: u8,
+    pub window: u16,
+    pub checksum: u16,
+    pub urgent_pointer: u16,
+    /// Raw TCP options.
+    pub options: Vec<u8>,
+}
+
+impl TcpHeader {
+    pub const FIN: u8 = 0x01;
+    pub const SYN: u8 = 0x02;
+    pub const RST: u8 = 0x04;
+    pub const PSH: u8 = 0x08;
+    pub const ACK: u8 = 0x10;
+    pub const URG: u8 = 0x20;
+    pub const ECE: u8 = 0x40;
+    pub const CWR: u8 = 0x80;
+
+    /// Decodes the TCP header at the start of `data`, returns `None` if it is truncated.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 20 {
+            return None;
+        }
+        let header_length = ((data[12] >> 4) as usize) * 4;
+        if header_length < 20 || data.len() < header_length {
+            return None;
+        }
+        Some(TcpHeader {
+            src_port: u16::from_be_bytes([data[0], data[1]]),
+            dst_port: u16::from_be_bytes([data[2], data[3]]),
+            seq: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            ack: u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+            header_length,
+            flags: data[13],
+            window: u16::from_be_bytes([data[14], data[15]]),
+            checksum: u16::from_be_bytes([data[16], data[17]]),
+            urgent_pointer: u16::from_be_bytes([data[18], data[19]]),
+            options: data[20..header_length].to_vec(),
+        })
+    }
+
+    /// Returns true if all the given flags are set.
+    pub fn has_flags(&self, flags: u8) -> bool {
+        self.flags & flags == flags
+    }
+}
+
+/// The header of an ICMP or ICMPv6 message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IcmpHeader {
+    pub icmp_type: u8,
+    pub icmp_code: u8,
+    pub checksum: u16,
+    /// The 4 bytes following the checksum, whose meaning depend on the message type.
+    pub rest_of_header: [u8; 4],
+}
+
+impl IcmpHeader {
+    /// Decodes the ICMP header at the start of `data`, returns `None` if it is truncated.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        Some(IcmpHeader {
+            icmp_type: data[0],
+            icmp_code: data[1],
+            checksum: u16::from_be_bytes([data[2], data[3]]),
+            rest_of_header: [data[4], data[5], data[6], data[7]],
+        })
+    }
+
+    /// Identifier, for echo requests and replies.
+    pub fn id(&self) -> u16 {
+        u16::from_be_bytes([self.rest_of_header[0], self.rest_of_header[1]])
+    }
+
+    /// Sequence number, for echo requests and replies.
+    pub fn seq(&self) -> u16 {
+        u16::from_be_bytes([self.rest_of_header[2], self.rest_of_header[3]])
+    }
+}
+
+/// Decodes the IP and ICMP headers of a raw IP packet, and returns them along with the
+/// ICMP payload. Returns `None` if the packet is truncated or is not an ICMP message.
+pub fn parse_icmp_packet(data: &[u8]) -> Option<(IpHeader, IcmpHeader, &[u8])> {
+    let ip = IpHeader::parse(data)?;
+    match (ip.version, ip.protocol) {
+        (4, IPPROTO_ICMP) | (6, IPPROTO_ICMPV6) => {}
+        _ => return None,
+    }
+    let end = (ip.total_length as usize).clamp(ip.header_length, data.len());
+    let message = &data[ip.header_length..end];
+    let icmp = IcmpHeader::parse(message)?;
+    Some((ip, icmp, &message[8..]))
+}
+
+/// Decodes the IP and TCP headers of a raw IP packet, and returns them along with the
+/// TCP payload. Returns `None` if the packet is truncated or is not a TCP segment.
+pub fn parse_tcp_packet(data: &[u8]) -> Option<(IpHeader, TcpHeader, &[u8])> {
+    let ip = IpHeader::parse(data)?;
+    if ip.protocol != IPPROTO_TCP {
+        return None;
+    }
+    // Ignore the link-layer padding, if any.
+    let end = (ip.total_length as usize).clamp(ip.header_length, data.len());
+    let segment = &data[ip.header_length..end];
+    let tcp = TcpHeader::parse(segment)?;
+    let payload = &segment[tcp.header_length..];
+    Some((ip, tcp, payload))
+}
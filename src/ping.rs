@@ -1,8 +1,109 @@
-use crate::{Address, Flags, Timeval};
+use crate::object::AddressSlot;
+use crate::{Address, Flags, IcmpKind, IpProtocol, StopCause, Timeval, WartsSized};
+use bitflags::bitflags;
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::Endian;
 use deku::prelude::*;
+use std::time::Duration;
+
+/// Ping probing method.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum PingMethod {
+    /// ICMP echo requests.
+    #[deku(id = "0x00")]
+    ICMPEcho,
+    /// TCP ACK packets.
+    #[deku(id = "0x01")]
+    TCPAck,
+    /// TCP ACK packets, varying the source port.
+    #[deku(id = "0x02")]
+    TCPAckSport,
+    /// UDP packets.
+    #[deku(id = "0x03")]
+    UDP,
+    /// UDP packets, varying the destination port.
+    #[deku(id = "0x04")]
+    UDPDport,
+    /// ICMP timestamp requests.
+    #[deku(id = "0x05")]
+    ICMPTime,
+    /// TCP SYN packets.
+    #[deku(id = "0x06")]
+    TCPSyn,
+    /// TCP SYN/ACK packets.
+    #[deku(id = "0x07")]
+    TCPSynAck,
+    /// TCP RST packets.
+    #[deku(id = "0x08")]
+    TCPRst,
+    /// TCP SYN packets, varying the source port.
+    #[deku(id = "0x09")]
+    TCPSynSport,
+    /// UDP packets, varying the source port.
+    #[deku(id = "0x0a")]
+    UDPSport,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+bitflags! {
+    /// Ping options.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PingFlags: u32 {
+        /// Include an IPv4 record route option.
+        const V4RR = 0x01;
+        /// Spoof the source address.
+        const SPOOF = 0x02;
+        /// A probe payload was specified.
+        const PAYLOAD = 0x04;
+        /// Include an IPv4 timestamp option, recording only timestamps.
+        const TSONLY = 0x08;
+        /// Include an IPv4 timestamp option, recording timestamps and addresses.
+        const TSANDADDR = 0x10;
+        /// An ICMP checksum was specified.
+        const ICMPSUM = 0x20;
+        /// Use a datalink socket.
+        const DL = 0x40;
+        /// Use the too-big trick.
+        const TBT = 0x80;
+        /// Do not embed the source address in the probes.
+        const NOSRC = 0x100;
+        /// Send probes using raw sockets.
+        const RAW = 0x200;
+        /// Receive replies using sockets rather than the datalink.
+        const SOCKRX = 0x400;
+    }
+}
+
+bitflags! {
+    /// Properties of a ping reply.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PingReplyFlags: u8 {
+        /// The reply TTL is included.
+        const REPLY_TTL = 0x01;
+        /// The reply IP ID is included.
+        const REPLY_IPID = 0x02;
+        /// The probe IP ID is included.
+        const PROBE_IPID = 0x04;
+        /// The probe was timestamped by the datalink on transmission.
+        const DLTX = 0x08;
+        /// The reply was timestamped by the datalink on receipt.
+        const DLRX = 0x10;
+        /// The reply TOS is included.
+        const REPLY_TOS = 0x20;
+    }
+}
+
+impl_deku_bitflags!(PingFlags, u32);
+impl_deku_bitflags!(PingReplyFlags, u8);
 
 /// Reason for the termination of a ping command.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
 pub enum PingStopReason {
     /// Null reason.
@@ -16,14 +117,18 @@ pub enum PingStopReason {
 }
 
 /// A ping.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct Ping {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
     /// Flags describing traceroute parameters and high-level outcomes.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     /// Parameter length, included if any flags are set.
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// List ID assigned by warts, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
@@ -71,9 +176,8 @@ pub struct Ping {
     #[deku(cond = "flags.get(15)")]
     pub pings_sent: Option<u16>,
     /// Ping method, included if flag 16 is set.
-    // TODO: Enum?
     #[deku(cond = "flags.get(16)")]
-    pub ping_method: Option<u8>,
+    pub ping_method: Option<PingMethod>,
     /// Probe source port, included if flag 17 is set.
     #[deku(cond = "flags.get(17)")]
     pub src_port: Option<u16>,
@@ -89,17 +193,19 @@ pub struct Ping {
     /// Destination address used, included if flag 21 is set.
     #[deku(cond = "flags.get(21)")]
     pub dst_addr: Option<Address>,
-    /// Ping flags, included if flag 22 is set.
-    // TODO: Enum?
-    #[deku(cond = "flags.get(22)")]
-    pub ping_flags1: Option<u8>,
+    /// Ping flags stored on one byte, included if flag 22 is set.
+    #[deku(
+        cond = "flags.get(22)",
+        reader = "read_short_ping_flags(deku::rest, endian)",
+        writer = "write_short_ping_flags(deku::output, ping_flags1, endian)"
+    )]
+    pub ping_flags1: Option<PingFlags>,
     /// Probe TOS, included if flag 23 is set.
     #[deku(cond = "flags.get(23)")]
     pub probe_tos: Option<u8>,
-    /// Probe Pre-specified timestamp option, included if flag 24 is set.
-    // TODO
-    #[deku(cond = "flags.get(24)", count = "0")]
-    pub tsprespec: Vec<u8>,
+    /// Probe pre-specified timestamp option, included if flag 24 is set.
+    #[deku(cond = "flags.get(24)")]
+    pub tsprespec: Option<TimestampPrespec>,
     /// Probe ICMP checksum, included if flag 25 is set.
     #[deku(cond = "flags.get(25)")]
     pub icmp_checksum: Option<u16>,
@@ -115,12 +221,12 @@ pub struct Ping {
     /// Probe TCP acknowledgment value, included if flag 29 is set.
     #[deku(cond = "flags.get(29)")]
     pub tcp_ack: Option<u32>,
-    /// Ping flags, included if flag 30 is set.
+    /// Ping flags stored on four bytes, included if flag 30 is set.
     #[deku(cond = "flags.get(30)")]
-    pub ping_flags2: Option<Address>,
+    pub ping_flags2: Option<PingFlags>,
     /// Probe TCP sequence number value, included if flag 31 is set.
     #[deku(cond = "flags.get(31)")]
-    pub tcp_seq: Option<Address>,
+    pub tcp_seq: Option<u32>,
     /// Router address used to send probes, included if flag 32 is set.
     #[deku(cond = "flags.get(32)")]
     pub router_addr: Option<Address>,
@@ -131,18 +237,42 @@ pub struct Ping {
     pub reply: Vec<PingProbe>,
 }
 
+/// Reads ping flags stored on one byte.
+fn read_short_ping_flags(
+    rest: &BitSlice<u8, Msb0>,
+    endian: Endian,
+) -> Result<(&BitSlice<u8, Msb0>, Option<PingFlags>), DekuError> {
+    let (rest, bits) = u8::read(rest, endian)?;
+    Ok((rest, Some(PingFlags::from_bits_retain(bits as u32))))
+}
+
+/// Writes ping flags stored on one byte.
+fn write_short_ping_flags(
+    output: &mut BitVec<u8, Msb0>,
+    value: &Option<PingFlags>,
+    endian: Endian,
+) -> Result<(), DekuError> {
+    if let Some(flags) = value {
+        (flags.bits() as u8).write(output, endian)?;
+    }
+    Ok(())
+}
+
 /// A ping probe and its associated reply, if any.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct PingProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     #[deku(cond = "flags.get(1)")]
     pub addr_id: Option<u32>,
-    // TODO
+    /// Reply flags, included if flag 2 is set.
     #[deku(cond = "flags.get(2)")]
-    pub flags2: Option<u8>,
+    pub flags2: Option<PingReplyFlags>,
     #[deku(cond = "flags.get(3)")]
     pub reply_ttl: Option<u8>,
     #[deku(cond = "flags.get(4)")]
@@ -159,21 +289,463 @@ pub struct PingProbe {
     pub reply_ipid: Option<u16>,
     #[deku(cond = "flags.get(9)")]
     pub probe_ipid: Option<u16>,
-    // TODO: Protocol enumeration? Already existing in Rust stdlib?
+    /// Protocol of the reply, included if flag 10 is set.
     #[deku(cond = "flags.get(10)")]
-    pub reply_proto: Option<u8>,
+    pub reply_proto: Option<IpProtocol>,
     #[deku(cond = "flags.get(11)")]
     pub tcp_flags: Option<u8>,
     #[deku(cond = "flags.get(12)")]
     pub addr: Option<Address>,
-    //TODO
+    /// IP record route option of the reply, included if flag 13 is set.
     #[deku(cond = "flags.get(13)")]
-    pub rr: Option<u8>,
+    pub rr: Option<RecordRoute>,
+    /// IP timestamp option of the reply, included if flag 14 is set.
     #[deku(cond = "flags.get(14)")]
-    pub ts: Option<u8>,
+    pub ts: Option<TimestampOption>,
     #[deku(cond = "flags.get(15)")]
     pub reply_ipid32: Option<u32>,
     #[deku(cond = "flags.get(16)")]
     pub tx: Option<Timeval>,
-    // TODO: tsreply
+    /// ICMP timestamp reply, included if flag 17 is set.
+    #[deku(cond = "flags.get(17)")]
+    pub tsreply: Option<TimestampReply>,
+}
+
+/// IP record route option found in a reply.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct RecordRoute {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub address_count: u8,
+    /// Addresses recorded by the routers on the forward and reverse paths, in order.
+    #[deku(count = "address_count")]
+    pub addresses: Vec<Address>,
+}
+
+/// Addresses pre-specified in the IP timestamp option of the probes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TimestampPrespec {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub address_count: u8,
+    #[deku(count = "address_count")]
+    pub addresses: Vec<Address>,
+}
+
+/// IP timestamp option found in a reply.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TimestampOption {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub timestamp_count: u8,
+    /// Number of addresses, either zero or the number of timestamps.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub address_count: u8,
+    /// Timestamps, in milliseconds since midnight UTC.
+    #[deku(count = "timestamp_count")]
+    pub timestamps: Vec<u32>,
+    /// Addresses of the routers that filled the timestamps, if recorded.
+    #[deku(count = "address_count")]
+    pub addresses: Vec<Address>,
+}
+
+/// Timestamps of an ICMP timestamp reply, in milliseconds since midnight UTC.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TimestampReply {
+    /// Time the request was sent.
+    pub originate: u32,
+    /// Time the request was received.
+    pub receive: u32,
+    /// Time the reply was sent.
+    pub transmit: u32,
+}
+
+impl RecordRoute {
+    pub fn new(addresses: Vec<Address>) -> Self {
+        RecordRoute {
+            address_count: addresses.len() as u8,
+            addresses,
+        }
+    }
+
+    /// Returns the addresses recorded after the destination, that is on the reverse path.
+    /// Returns `None` if the destination did not record itself (e.g. the option was full).
+    pub fn reverse_path(&self, dst_addr: &Address) -> Option<&[Address]> {
+        let index = self.addresses.iter().position(|addr| addr == dst_addr)?;
+        Some(&self.addresses[index + 1..])
+    }
+}
+
+impl TimestampPrespec {
+    pub fn new(addresses: Vec<Address>) -> Self {
+        TimestampPrespec {
+            address_count: addresses.len() as u8,
+            addresses,
+        }
+    }
+}
+
+impl TimestampOption {
+    pub fn new(timestamps: Vec<u32>, addresses: Vec<Address>) -> Self {
+        TimestampOption {
+            timestamp_count: timestamps.len() as u8,
+            address_count: addresses.len() as u8,
+            timestamps,
+            addresses,
+        }
+    }
+
+    /// Returns true if only timestamps were recorded (`-T tsonly`).
+    pub fn is_timestamp_only(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    /// Returns the timestamp slots, each made of the address of the router that filled it
+    /// (if recorded) and of the timestamp.
+    pub fn slots(&self) -> impl Iterator<Item = (Option<&Address>, u32)> {
+        self.timestamps
+            .iter()
+            .enumerate()
+            .map(|(i, ts)| (self.addresses.get(i), *ts))
+    }
+
+    /// Returns the timestamp filled by the given address, if any.
+    pub fn timestamp_of(&self, address: &Address) -> Option<u32> {
+        self.slots()
+            .find(|(addr, _)| *addr == Some(address))
+            .map(|(_, ts)| ts)
+    }
+}
+
+impl WartsSized for RecordRoute {
+    fn warts_size(&self) -> usize {
+        self.address_count.warts_size() + self.addresses.warts_size()
+    }
+}
+
+impl WartsSized for TimestampPrespec {
+    fn warts_size(&self) -> usize {
+        self.address_count.warts_size() + self.addresses.warts_size()
+    }
+}
+
+impl WartsSized for TimestampOption {
+    fn warts_size(&self) -> usize {
+        self.timestamp_count.warts_size()
+            + self.address_count.warts_size()
+            + self.timestamps.warts_size()
+            + self.addresses.warts_size()
+    }
+}
+
+impl WartsSized for TimestampReply {
+    fn warts_size(&self) -> usize {
+        12
+    }
+}
+
+impl Ping {
+    /// Returns the ping flags, from whichever of the one-byte and four-byte fields is present.
+    pub fn ping_flags(&self) -> PingFlags {
+        self.ping_flags2
+            .or(self.ping_flags1)
+            .unwrap_or_else(PingFlags::empty)
+    }
+
+    /// Returns the probing method, which is omitted from the parameters when it is ICMP echo.
+    pub fn method(&self) -> PingMethod {
+        self.ping_method.unwrap_or(PingMethod::ICMPEcho)
+    }
+
+    /// Returns why the ping stopped, if a stop reason was recorded.
+    pub fn stop_cause(&self) -> Option<StopCause> {
+        Some(StopCause::from_ping(
+            self.stop_reason?,
+            self.stop_data.unwrap_or(0),
+        ))
+    }
+
+    /// Address fields of the parameters, pre-specified timestamps and replies, including
+    /// the addresses of the IP options recorded in the replies.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        let mut slots = vec![
+            AddressSlot::Optional(&mut self.src_addr, self.src_addr_id),
+            AddressSlot::Optional(&mut self.dst_addr, self.dst_addr_id),
+        ];
+        if let Some(tsprespec) = &mut self.tsprespec {
+            slots.extend(tsprespec.addresses.iter_mut().map(AddressSlot::Required));
+        }
+        slots.push(AddressSlot::Optional(&mut self.router_addr, None));
+        for reply in &mut self.reply {
+            slots.push(AddressSlot::Optional(&mut reply.addr, reply.addr_id));
+            if let Some(rr) = &mut reply.rr {
+                slots.extend(rr.addresses.iter_mut().map(AddressSlot::Required));
+            }
+            if let Some(ts) = &mut reply.ts {
+                slots.extend(ts.addresses.iter_mut().map(AddressSlot::Required));
+            }
+        }
+        slots
+    }
+
+    /// Returns true if the reply comes from the destination of the ping.
+    /// The addresses must have been resolved with [`Object::dereference`](crate::Object::dereference).
+    pub fn is_reply_from_target(&self, reply: &PingProbe) -> bool {
+        reply.addr.is_some() && reply.addr == self.dst_addr
+    }
+}
+
+impl WartsSized for PingMethod {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl PingProbe {
+    /// Round trip time of the ping probe, or `None` if it was not answered.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt_usec.map(|x| Duration::from_micros(x as u64))
+    }
+
+    /// Time at which the echo (or other) reply to this probe arrived, or `None` if the probe
+    /// was not answered or its transmit time was not recorded.
+    pub fn rx(&self) -> Option<Timeval> {
+        self.tx?.checked_add(self.rtt()?)
+    }
+
+    /// Classifies the ICMP message answering this ping probe. The reply protocol tells
+    /// ICMPv4 and ICMPv6 apart when recorded, the family of the reply source address
+    /// otherwise.
+    pub fn icmp_kind(&self) -> Option<IcmpKind> {
+        match self.reply_proto {
+            Some(IpProtocol::ICMP) => Some(IcmpKind::new(self.icmp_type?, self.icmp_code?, false)),
+            Some(IpProtocol::ICMPv6) => Some(IcmpKind::new(self.icmp_type?, self.icmp_code?, true)),
+            _ => IcmpKind::from_reply(self.icmp_type, self.icmp_code, self.addr.as_ref()),
+        }
+    }
+
+    /// Returns the reply IP ID, from whichever of the 16-bit and 32-bit fields is present.
+    pub fn reply_ipid(&self) -> Option<u32> {
+        self.reply_ipid32
+            .or_else(|| self.reply_ipid.map(|ipid| ipid as u32))
+    }
+
+    /// Returns true if the probe was timestamped by the datalink on transmission.
+    pub fn tx_timestamped(&self) -> bool {
+        self.flags2
+            .is_some_and(|flags| flags.contains(PingReplyFlags::DLTX))
+    }
+
+    /// Addresses of the reverse path recorded in the reply, from the record route option
+    /// or, failing that, from the addresses of the timestamp option that follow the destination.
+    pub fn reverse_path_hints(&self, dst_addr: &Address) -> Vec<Address> {
+        if let Some(rr) = &self.rr {
+            if let Some(addresses) = rr.reverse_path(dst_addr) {
+                return addresses.to_vec();
+            }
+        }
+        self.ts
+            .iter()
+            .flat_map(|ts| {
+                ts.addresses
+                    .iter()
+                    .skip_while(|addr| *addr != dst_addr)
+                    .skip(1)
+            })
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, Flags, IpProtocol, Object, Ping, PingFlags, PingMethod, PingProbe, PingReplyFlags,
+        RecordRoute, TimestampOption, TimestampPrespec,
+    };
+    use deku::bitvec::{BitVec, BitView};
+    use deku::ctx::Endian;
+    use deku::prelude::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn timestamp_options() {
+        let a = Address::from(Ipv4Addr::new(192, 0, 2, 1));
+        let b = Address::from(Ipv4Addr::new(192, 0, 2, 2));
+        let prespec = TimestampPrespec::new(vec![a, b]);
+        let mut output = BitVec::new();
+        prespec.write(&mut output, Endian::Big).unwrap();
+        let bytes = output.into_vec();
+        assert_eq!(bytes[0], 2);
+        let (rest, parsed) = TimestampPrespec::read(bytes.view_bits(), Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(parsed, prespec);
+
+        // Reply with flag 14 set: tsc = 1, ipc = 1, one timestamp filled by 192.0.2.1.
+        let mut data = vec![0x80, 0x40, 0x00, 0x0c];
+        data.extend_from_slice(&[0x01, 0x01, 0x03, 0x2a, 0x63, 0x80]);
+        data.extend_from_slice(&[0x04, 0x01, 0xc0, 0x00, 0x02, 0x01]);
+        let (rest, probe) = PingProbe::read(data.view_bits(), Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        let ts: &TimestampOption = probe.ts.as_ref().unwrap();
+        assert_eq!(ts.slots().collect::<Vec<_>>(), vec![(Some(&a), 53_109_632)]);
+        assert_eq!(ts.timestamp_of(&a), Some(53_109_632));
+        assert_eq!(ts.timestamp_of(&b), None);
+        let mut output = BitVec::new();
+        probe.write(&mut output, Endian::Big).unwrap();
+        assert_eq!(output.into_vec(), data);
+    }
+
+    #[test]
+    fn reply_protocol() {
+        // Replies with flag 10 set: UDP, then a protocol not known to this crate.
+        for (proto, expected) in [(0x11, IpProtocol::UDP), (0x84, IpProtocol::Unknown(0x84))] {
+            let data = vec![0x80, 0x04, 0x00, 0x01, proto];
+            let (rest, probe) = PingProbe::read(data.view_bits(), Endian::Big).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(probe.reply_proto, Some(expected));
+            let mut output = BitVec::new();
+            probe.write(&mut output, Endian::Big).unwrap();
+            assert_eq!(output.into_vec(), data);
+        }
+    }
+
+    #[test]
+    fn record_route() {
+        let hops: Vec<Address> = [1, 2, 3, 2, 1]
+            .iter()
+            .map(|i| Address::from(Ipv4Addr::new(192, 0, 2, *i)))
+            .collect();
+        // Reply with flags 13 and 14 set: five recorded addresses, and a tsonly option.
+        let mut data = vec![0x80, 0x60, 0x00, 0x25, 0x05];
+        for i in [1, 2, 3, 2, 1] {
+            data.extend_from_slice(&[0x04, 0x01, 0xc0, 0x00, 0x02, i]);
+        }
+        data.extend_from_slice(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x2a]);
+        let (rest, probe) = PingProbe::read(data.view_bits(), Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(probe.rr, Some(RecordRoute::new(hops.clone())));
+        assert_eq!(probe.ts, Some(TimestampOption::new(vec![42], vec![])));
+        assert!(probe.ts.as_ref().unwrap().is_timestamp_only());
+        assert_eq!(probe.reverse_path_hints(&hops[2]), hops[3..].to_vec());
+        assert_eq!(
+            probe.reverse_path_hints(&Address::from(Ipv4Addr::new(198, 51, 100, 1))),
+            vec![]
+        );
+        let mut output = BitVec::new();
+        probe.write(&mut output, Endian::Big).unwrap();
+        assert_eq!(output.into_vec(), data);
+    }
+
+    fn ping() -> Ping {
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        Object::all_from_bytes(data)
+            .into_iter()
+            .find_map(|object| match object {
+                Object::Ping(p) => Some(p),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn dereference_prespecified_timestamps() {
+        let mut ping = ping();
+        let (src_addr, dst_addr) = (ping.src_addr.unwrap(), ping.dst_addr.unwrap());
+        // The address table is made of the source, the destination, then the new
+        // pre-specified address, which comes before the reply addresses on the wire.
+        let a = Address::from(Ipv4Addr::new(192, 0, 2, 9));
+        ping.tsprespec = Some(TimestampPrespec::new(vec![a, Address::Reference(0)]));
+        ping.reply[0].addr = Some(Address::Reference(2));
+        ping.reply[1].addr = Some(Address::Reference(1));
+        let mut object = Object::Ping(ping);
+        object.dereference();
+        let Object::Ping(ping) = object else {
+            unreachable!()
+        };
+        assert_eq!(ping.tsprespec.unwrap().addresses, vec![a, src_addr]);
+        assert_eq!(ping.reply[0].addr, Some(a));
+        assert_eq!(ping.reply[1].addr, Some(dst_addr));
+    }
+
+    #[test]
+    fn dereference_record_route() {
+        let mut ping = ping();
+        let dst_addr = ping.dst_addr.unwrap();
+        let router = Address::from(Ipv4Addr::new(192, 0, 2, 254));
+        let hops: Vec<Address> = [1, 2]
+            .iter()
+            .map(|i| Address::from(Ipv4Addr::new(198, 51, 100, *i)))
+            .collect();
+        // Table: source, destination, router, first reply (the destination, by reference),
+        // its recorded route and timestamp addresses, then the second reply.
+        ping.router_addr = Some(router);
+        ping.reply[0].addr = Some(Address::Reference(1));
+        ping.reply[0].rr = Some(RecordRoute::new(vec![
+            hops[0],
+            hops[1],
+            Address::Reference(1),
+            Address::Reference(4),
+        ]));
+        ping.reply[0].ts = Some(TimestampOption::new(vec![42], vec![Address::Reference(3)]));
+        ping.reply[1].addr = Some(Address::Reference(4));
+        ping.reply[1].rr = Some(RecordRoute::new(vec![Address::Reference(2)]));
+        let mut object = Object::Ping(ping);
+        object.dereference();
+        let Object::Ping(ping) = object else {
+            unreachable!()
+        };
+        assert_eq!(ping.router_addr, Some(router));
+        assert_eq!(ping.reply[0].addr, Some(dst_addr));
+        assert_eq!(
+            ping.reply[0].rr.as_ref().unwrap().addresses,
+            vec![hops[0], hops[1], dst_addr, hops[1]]
+        );
+        assert_eq!(ping.reply[0].ts.as_ref().unwrap().addresses, vec![hops[0]]);
+        assert_eq!(ping.reply[1].addr, Some(hops[1]));
+        assert_eq!(ping.reply[1].rr.as_ref().unwrap().addresses, vec![router]);
+    }
+
+    #[test]
+    fn method_and_flags() {
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        let mut objects = Object::all_from_bytes(data);
+        let object = objects
+            .iter_mut()
+            .find(|o| matches!(o, Object::Ping(_)))
+            .unwrap();
+        object.dereference();
+        let Object::Ping(ping) = object else {
+            unreachable!()
+        };
+        assert_eq!(ping.method(), PingMethod::ICMPEcho);
+        assert_eq!(ping.ping_flags(), PingFlags::empty());
+        assert_eq!(ping.reply.len(), 4);
+        for reply in &ping.reply {
+            assert!(ping.is_reply_from_target(reply));
+            assert!(reply.flags2.unwrap().contains(PingReplyFlags::REPLY_TTL));
+            assert!(!reply.tx_timestamped());
+            assert_eq!(reply.reply_ipid(), reply.reply_ipid.map(u32::from));
+        }
+
+        // Add one-byte ping flags (flag 22) and check that they survive a round trip.
+        let mut ping = ping.clone();
+        let mut indices: Vec<i32> = (1..=32).filter(|&i| ping.flags.get(i as usize)).collect();
+        indices.push(22);
+        ping.flags = Flags::from(indices);
+        ping.param_length = ping.param_length.map(|length| length + 1);
+        ping.length += 1;
+        ping.ping_flags1 = Some(PingFlags::V4RR | PingFlags::TBT);
+        let bytes = Object::Ping(ping.clone()).to_bytes().unwrap();
+        let parsed = Object::all_from_bytes(&bytes);
+        let Object::Ping(parsed) = &parsed[0] else {
+            unreachable!()
+        };
+        assert_eq!(parsed.ping_flags(), PingFlags::V4RR | PingFlags::TBT);
+    }
 }
@@ -0,0 +1,120 @@
+//! Serde representations for types that do not have a suitable one by default.
+
+/// A [`CString`](std::ffi::CString) represented as an UTF-8 string, or as a byte array
+/// if it is not valid UTF-8, in human-readable formats; always as bytes in the others.
+pub(crate) mod cstring {
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::ffi::CString;
+    use std::fmt::Formatter;
+
+    pub fn serialize<S: Serializer>(value: &CString, serializer: S) -> Result<S::Ok, S::Error> {
+        match value.to_str() {
+            Ok(s) if serializer.is_human_readable() => serializer.serialize_str(s),
+            _ => serializer.serialize_bytes(value.as_bytes()),
+        }
+    }
+
+    /// Self-describing formats such as JSON accept either a string or a byte array here.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CString, D::Error> {
+        deserializer.deserialize_bytes(CStringVisitor)
+    }
+
+    struct CStringVisitor;
+
+    impl<'de> Visitor<'de> for CStringVisitor {
+        type Value = CString;
+
+        fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+            formatter.write_str("a string or a byte array without interior nul bytes")
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            self.visit_bytes(v.as_bytes())
+        }
+
+        fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            CString::new(v).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::new();
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            CString::new(bytes).map_err(A::Error::custom)
+        }
+    }
+}
+
+/// An optional [`CString`](std::ffi::CString), see [`cstring`].
+pub(crate) mod option_cstring {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::ffi::CString;
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "super::cstring")] CString);
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<CString>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .as_ref()
+            .map(|s| Wrapper(s.clone()))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<CString>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|w| w.0))
+    }
+}
+
+/// A vector of [`CString`](std::ffi::CString), see [`cstring`].
+pub(crate) mod vec_cstring {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::ffi::CString;
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "super::cstring")] CString);
+
+    pub fn serialize<S: Serializer>(value: &[CString], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter().map(|s| Wrapper(s.clone())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<CString>, D::Error> {
+        Ok(Vec::<Wrapper>::deserialize(deserializer)?
+            .into_iter()
+            .map(|w| w.0)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use std::ffi::CString;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Test {
+        #[serde(with = "super::cstring")]
+        a: CString,
+        #[serde(with = "super::option_cstring")]
+        b: Option<CString>,
+    }
+
+    #[test]
+    fn cstring_round_trip() {
+        let value = Test {
+            a: CString::new("default").unwrap(),
+            b: Some(CString::new(vec![0xff, 0x41]).unwrap()),
+        };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"a":"default","b":[255,65]}"#);
+        assert_eq!(serde_json::from_str::<Test>(&json).unwrap(), value);
+    }
+}
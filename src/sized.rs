@@ -25,12 +25,24 @@ impl WartsSized for u32 {
     }
 }
 
+impl WartsSized for i32 {
+    fn warts_size(&self) -> usize {
+        size_of_val(self)
+    }
+}
+
 impl WartsSized for CString {
     fn warts_size(&self) -> usize {
         self.to_bytes_with_nul().len()
     }
 }
 
+impl<T: WartsSized> WartsSized for Vec<T> {
+    fn warts_size(&self) -> usize {
+        self.iter().map(|x| x.warts_size()).sum()
+    }
+}
+
 impl<T: WartsSized> WartsSized for &T {
     fn warts_size(&self) -> usize {
         (*self).warts_size()
@@ -0,0 +1,271 @@
+use crate::object::AddressSlot;
+use crate::{
+    parse_icmp_packet, Address, Flags, IcmpHeader, IpHeader, Measurement, Timeval, WartsSized,
+};
+use deku::prelude::*;
+
+/// Reason why a sniff measurement stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum SniffStopReason {
+    #[deku(id = "0x00")]
+    None,
+    #[deku(id = "0x01")]
+    Error,
+    /// The time limit was reached.
+    #[deku(id = "0x02")]
+    LimitTime,
+    /// The packet count limit was reached.
+    #[deku(id = "0x03")]
+    LimitPacketCount,
+    #[deku(id = "0x04")]
+    Halted,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// A sniff measurement, which captures the ICMP packets received on an interface.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct Sniff {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the list that requested the capture, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// ID of the cycle during which packets were captured, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// User-supplied identifier of the capture, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Address of the interface to capture packets on, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub src_addr: Option<Address>,
+    /// Time capture commenced, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub start_time: Option<Timeval>,
+    /// Time capture finished, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub finish_time: Option<Timeval>,
+    /// Reason why the capture stopped, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub stop_reason: Option<SniffStopReason>,
+    /// Maximum number of packets to capture, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub limit_packet_count: Option<u32>,
+    /// Maximum capture duration in seconds, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub limit_time: Option<u16>,
+    /// Number of packets captured, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub packet_count: Option<u32>,
+    /// ICMP ID of the packets to capture, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub icmp_id: Option<u16>,
+    /// Packets captured.
+    #[deku(count = "packet_count.unwrap_or(0)")]
+    pub packets: Vec<SniffPacket>,
+}
+
+/// A packet captured during a sniff measurement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct SniffPacket {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Time the packet was captured, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub timestamp: Option<Timeval>,
+    /// Length of the packet, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub data_length: Option<u16>,
+    /// Raw IP packet, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)", count = "data_length.unwrap_or(0)")]
+    pub data: Option<Vec<u8>>,
+}
+
+impl Sniff {
+    /// Address of the capture interface, the only address stored with a sniff measurement.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        vec![AddressSlot::Optional(&mut self.src_addr, None)]
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.packet_count = Some(self.packets.len() as u32);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.src_addr);
+        push_flag!(flags, param_length, 5, self.start_time);
+        push_flag!(flags, param_length, 6, self.finish_time);
+        push_flag!(flags, param_length, 7, self.stop_reason);
+        push_flag!(flags, param_length, 8, self.limit_packet_count);
+        push_flag!(flags, param_length, 9, self.limit_time);
+        push_flag!(flags, param_length, 10, self.packet_count);
+        push_flag!(flags, param_length, 11, self.icmp_id);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.packets.warts_size()) as u32;
+        self
+    }
+}
+
+impl Measurement for Sniff {
+    fn list_id(&self) -> Option<u32> {
+        self.list_id
+    }
+    fn cycle_id(&self) -> Option<u32> {
+        self.cycle_id
+    }
+    fn user_id(&self) -> Option<u32> {
+        self.user_id
+    }
+    fn start_timeval(&self) -> Option<&Timeval> {
+        self.start_time.as_ref()
+    }
+    fn src_address(&self) -> Option<&Address> {
+        self.src_addr.as_ref()
+    }
+    fn dst_address(&self) -> Option<&Address> {
+        None
+    }
+}
+
+impl SniffPacket {
+    /// Decodes the IP header of the packet.
+    pub fn ip_header(&self) -> Option<IpHeader> {
+        IpHeader::parse(self.data.as_deref()?)
+    }
+
+    /// Decodes the ICMP header of the packet, if it is an ICMP message.
+    pub fn icmp_header(&self) -> Option<IcmpHeader> {
+        parse_icmp_packet(self.data.as_deref()?).map(|(_, icmp, _)| icmp)
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.data_length = self.data.as_ref().map(|data| data.len() as u16);
+        push_flag!(flags, param_length, 1, self.timestamp);
+        push_flag!(flags, param_length, 2, self.data_length);
+        push_flag!(flags, param_length, 3, self.data);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for SniffStopReason {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for SniffPacket {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Address, Object, Sniff, SniffPacket, SniffStopReason, Timeval};
+    use deku::bitvec::BitView;
+    use deku::prelude::*;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn round_trip() {
+        // ICMP echo reply from 8.8.8.8 to 192.0.2.1, ID 0x1234, sequence 7.
+        let v4 = vec![
+            0x45, 0x00, 0x00, 0x1c, 0x00, 0x00, 0x00, 0x00, 0x75, 0x01, 0x00, 0x00, 0x08, 0x08,
+            0x08, 0x08, 0xc0, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x07,
+        ];
+        // ICMPv6 time exceeded from 2001:db8::1 to 2001:db8::2, with no quoted packet.
+        let mut v6 = vec![0x60, 0x00, 0x00, 0x00, 0x00, 0x08, 0x3a, 0x40];
+        v6.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        v6.extend_from_slice(&"2001:db8::2".parse::<Ipv6Addr>().unwrap().octets());
+        v6.extend_from_slice(&[0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        let packets = [v4, v6]
+            .into_iter()
+            .map(|data| {
+                SniffPacket {
+                    flags: Default::default(),
+                    param_length: None,
+                    timestamp: Some(Timeval::new(1644339038, 0)),
+                    data_length: None,
+                    data: Some(data),
+                }
+                .finalize()
+            })
+            .collect();
+        let sniff = Sniff {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            finish_time: Some(Timeval::new(1644339048, 0)),
+            stop_reason: Some(SniffStopReason::LimitTime),
+            limit_packet_count: Some(100),
+            limit_time: Some(10),
+            packet_count: None,
+            icmp_id: Some(0x1234),
+            packets,
+        }
+        .finalize();
+        let bytes = Object::Sniff(sniff.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + sniff.length as usize);
+        let objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::Sniff(sniff.clone())]);
+
+        let ip = sniff.packets[0].ip_header().unwrap();
+        assert_eq!(ip.src_addr, IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)));
+        let icmp = sniff.packets[0].icmp_header().unwrap();
+        assert_eq!((icmp.icmp_type, icmp.id(), icmp.seq()), (0, 0x1234, 7));
+        let ip = sniff.packets[1].ip_header().unwrap();
+        assert_eq!(ip.ttl, 64);
+        assert_eq!(ip.total_length, 48);
+        let icmp = sniff.packets[1].icmp_header().unwrap();
+        assert_eq!((icmp.icmp_type, icmp.icmp_code), (3, 0));
+    }
+
+    #[test]
+    fn packet_data_parameter() {
+        // Flags 1 (time), 2 (length) and 3 (data), 14 bytes of parameters.
+        let input = [
+            0x07, 0x00, 0x0e, 0x62, 0x02, 0x46, 0x5e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x45,
+            0x00, 0x00, 0x1c,
+        ];
+        let (rest, packet) = SniffPacket::read(input.view_bits(), deku::ctx::Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(packet.timestamp, Some(Timeval::new(1644316254, 0)));
+        assert_eq!(packet.data, Some(vec![0x45, 0x00, 0x00, 0x1c]));
+        assert_eq!(packet.clone().finalize(), packet);
+    }
+}
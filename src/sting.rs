@@ -0,0 +1,334 @@
+use crate::object::AddressSlot;
+use crate::{parse_tcp_packet, Address, Flags, IpHeader, TcpHeader, Timeval, WartsSized};
+use deku::prelude::*;
+
+/// Distribution of the inter-packet times of the data packets.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum StingDistribution {
+    #[deku(id = "0x01")]
+    Exponential,
+    #[deku(id = "0x02")]
+    Periodic,
+    #[deku(id = "0x03")]
+    Uniform,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// Outcome of a sting measurement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum StingResult {
+    #[deku(id = "0x00")]
+    None,
+    #[deku(id = "0x01")]
+    Completed,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// A sting measurement, which infers the forward and reverse packet loss on a TCP connection.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct Sting {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the list the measurement belongs to, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// ID of the cycle in which the measurement was made, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// User-supplied identifier of the measurement, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Local address the TCP connection is opened from, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub src_addr: Option<Address>,
+    /// Address of the host whose forward and reverse loss is measured,
+    /// included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub dst_addr: Option<Address>,
+    /// Source port, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub src_port: Option<u16>,
+    /// Destination port, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub dst_port: Option<u16>,
+    /// Number of data packets to send, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub count: Option<u16>,
+    /// Mean time between data packets in milliseconds, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub mean: Option<u16>,
+    /// Time between the data seeding and the hole filling phases in milliseconds,
+    /// included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub inter: Option<u16>,
+    /// Distribution of the inter-packet times, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub distribution: Option<StingDistribution>,
+    /// Number of SYN retransmissions, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub syn_retx: Option<u8>,
+    /// Number of data retransmissions, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)")]
+    pub data_retx: Option<u8>,
+    /// Size of the initial hole in the sequence space, included if flag 14 is set.
+    #[deku(cond = "flags.get(14)")]
+    pub seq_skip: Option<u8>,
+    /// Length of the data, included if flag 15 is set.
+    #[deku(cond = "flags.get(15)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub data_length: Option<u16>,
+    /// Data sent in the data packets, included if flag 16 is set.
+    #[deku(cond = "flags.get(16)", count = "data_length.unwrap_or(0)")]
+    pub data: Option<Vec<u8>>,
+    /// Time measurement commenced, included if flag 17 is set.
+    #[deku(cond = "flags.get(17)")]
+    pub start_time: Option<Timeval>,
+    /// RTT of the handshake in microseconds, included if flag 18 is set.
+    #[deku(cond = "flags.get(18)")]
+    pub handshake_rtt: Option<u32>,
+    /// Number of data packets acknowledged during the data seeding phase,
+    /// included if flag 19 is set.
+    #[deku(cond = "flags.get(19)")]
+    pub data_ack_count: Option<u16>,
+    /// Number of holes filled during the hole filling phase, included if flag 20 is set.
+    #[deku(cond = "flags.get(20)")]
+    pub hole_count: Option<u16>,
+    /// Number of packets recorded, included if flag 21 is set.
+    #[deku(cond = "flags.get(21)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub packet_count: Option<u32>,
+    /// Outcome of the measurement, included if flag 22 is set.
+    #[deku(cond = "flags.get(22)")]
+    pub result: Option<StingResult>,
+    /// Packets sent and received during the measurement.
+    #[deku(count = "packet_count.unwrap_or(0)")]
+    pub packets: Vec<StingPacket>,
+}
+
+/// A packet sent or received during a sting measurement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct StingPacket {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Packet flags (TX 0x01, RX 0x02, data 0x04, hole 0x08), included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub packet_flags: Option<u8>,
+    /// Time the packet was sent or received, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub timestamp: Option<Timeval>,
+    /// Length of the packet, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub data_length: Option<u16>,
+    /// Raw IP packet, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)", count = "data_length.unwrap_or(0)")]
+    pub data: Option<Vec<u8>>,
+}
+
+impl Sting {
+    /// Local and remote addresses; sting packets are stored raw, without address fields.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        vec![
+            AddressSlot::Optional(&mut self.src_addr, None),
+            AddressSlot::Optional(&mut self.dst_addr, None),
+        ]
+    }
+
+    /// Fraction of the data packets lost on the forward path, inferred from the number
+    /// of holes filled during the hole filling phase.
+    pub fn forward_loss_rate(&self) -> Option<f64> {
+        let count = self.count.filter(|&count| count > 0)?;
+        Some(f64::from(self.hole_count?) / f64::from(count))
+    }
+
+    /// Fraction of the acknowledgments lost on the reverse path, among the data packets
+    /// that were not lost on the forward path.
+    pub fn reverse_loss_rate(&self) -> Option<f64> {
+        let received = self.count?.checked_sub(self.hole_count?)?;
+        if received == 0 {
+            return None;
+        }
+        let acked = self.data_ack_count?.min(received);
+        Some(f64::from(received - acked) / f64::from(received))
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.data_length = self.data.as_ref().map(|data| data.len() as u16);
+        self.packet_count = Some(self.packets.len() as u32);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.src_addr);
+        push_flag!(flags, param_length, 5, self.dst_addr);
+        push_flag!(flags, param_length, 6, self.src_port);
+        push_flag!(flags, param_length, 7, self.dst_port);
+        push_flag!(flags, param_length, 8, self.count);
+        push_flag!(flags, param_length, 9, self.mean);
+        push_flag!(flags, param_length, 10, self.inter);
+        push_flag!(flags, param_length, 11, self.distribution);
+        push_flag!(flags, param_length, 12, self.syn_retx);
+        push_flag!(flags, param_length, 13, self.data_retx);
+        push_flag!(flags, param_length, 14, self.seq_skip);
+        push_flag!(flags, param_length, 15, self.data_length);
+        push_flag!(flags, param_length, 16, self.data);
+        push_flag!(flags, param_length, 17, self.start_time);
+        push_flag!(flags, param_length, 18, self.handshake_rtt);
+        push_flag!(flags, param_length, 19, self.data_ack_count);
+        push_flag!(flags, param_length, 20, self.hole_count);
+        push_flag!(flags, param_length, 21, self.packet_count);
+        push_flag!(flags, param_length, 22, self.result);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.packets.warts_size()) as u32;
+        self
+    }
+}
+
+impl_measurement!(Sting);
+
+impl StingPacket {
+    /// Returns true if the packet was sent by scamper.
+    pub fn is_tx(&self) -> bool {
+        self.packet_flags.unwrap_or(0) & 0x01 != 0
+    }
+
+    /// Returns true if the packet was received by scamper.
+    pub fn is_rx(&self) -> bool {
+        self.packet_flags.unwrap_or(0) & 0x02 != 0
+    }
+
+    /// Decodes the IP header of the packet.
+    pub fn ip_header(&self) -> Option<IpHeader> {
+        IpHeader::parse(self.data.as_deref()?)
+    }
+
+    /// Decodes the TCP header of the packet, if it is a TCP segment.
+    pub fn tcp_header(&self) -> Option<TcpHeader> {
+        parse_tcp_packet(self.data.as_deref()?).map(|(_, tcp, _)| tcp)
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.data_length = self.data.as_ref().map(|data| data.len() as u16);
+        push_flag!(flags, param_length, 1, self.packet_flags);
+        push_flag!(flags, param_length, 2, self.timestamp);
+        push_flag!(flags, param_length, 3, self.data_length);
+        push_flag!(flags, param_length, 4, self.data);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for StingDistribution {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for StingResult {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for StingPacket {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Address, Object, Sting, StingDistribution, StingPacket, StingResult, Timeval};
+    use deku::bitvec::BitView;
+    use deku::prelude::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn round_trip() {
+        let packet = StingPacket {
+            flags: Default::default(),
+            param_length: None,
+            packet_flags: Some(0x01),
+            timestamp: Some(Timeval::new(1644339038, 0)),
+            data_length: None,
+            data: Some(vec![0x45, 0x00, 0x00, 0x14]),
+        }
+        .finalize();
+        let sting = Sting {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            dst_addr: Some(Address::from(Ipv4Addr::new(198, 51, 100, 1))),
+            src_port: Some(40000),
+            dst_port: Some(80),
+            count: Some(48),
+            mean: Some(100),
+            inter: Some(2000),
+            distribution: Some(StingDistribution::Uniform),
+            syn_retx: Some(3),
+            data_retx: Some(5),
+            seq_skip: Some(3),
+            data_length: None,
+            data: Some(b"GET / HTTP/1.0\r\n\r\n".to_vec()),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            handshake_rtt: Some(12_345),
+            data_ack_count: Some(44),
+            hole_count: Some(2),
+            packet_count: None,
+            result: Some(StingResult::Completed),
+            packets: vec![packet],
+        }
+        .finalize();
+        assert!(sting.packets[0].is_tx());
+        assert_eq!(sting.forward_loss_rate(), Some(2.0 / 48.0));
+        assert_eq!(sting.reverse_loss_rate(), Some(2.0 / 46.0));
+        let bytes = Object::Sting(sting.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + sting.length as usize);
+        let objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::Sting(sting)]);
+    }
+
+    #[test]
+    fn packet_data_parameter() {
+        // Flags 1 (packet flags), 3 (length) and 4 (data), 7 bytes of parameters.
+        let input = [0x0d, 0x00, 0x07, 0x02, 0x00, 0x04, 0x45, 0x00, 0x00, 0x14];
+        let (rest, packet) = StingPacket::read(input.view_bits(), deku::ctx::Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        assert!(packet.is_rx());
+        assert_eq!(packet.data, Some(vec![0x45, 0x00, 0x00, 0x14]));
+        assert_eq!(packet.clone().finalize(), packet);
+    }
+}
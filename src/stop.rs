@@ -0,0 +1,199 @@
+use crate::{IcmpKind, PingStopReason, TraceStopReason, UnreachableReason};
+use std::fmt::{Display, Formatter};
+
+/// Why a measurement stopped, combining the stop reason with the stop data that qualifies it.
+///
+/// Traceroutes and pings are covered; tracelb (MDA traceroutes) will be covered once its
+/// stop fields are decoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopCause {
+    /// No reason was recorded.
+    None,
+    /// The measurement ran to completion (e.g. the destination replied).
+    Completed,
+    /// A destination unreachable message was received.
+    Unreachable(UnreachableReason),
+    /// Another ICMP message was received, with its type and code. The code is not part of
+    /// the stop data, and is `None` when the reply of the stop hop is not of that type.
+    Icmp {
+        icmp_type: u8,
+        icmp_code: Option<u8>,
+    },
+    /// A loop was detected.
+    Loop,
+    /// Too many consecutive hops did not reply.
+    GapLimit,
+    /// Sending a probe failed, with the errno.
+    SendError(u8),
+    /// The maximum number of hops was reached.
+    HopLimit,
+    /// A hop in the global stop set was reached (Doubletree).
+    GlobalStopSet,
+    /// The measurement was halted.
+    Halted,
+}
+
+impl StopCause {
+    /// Combines a traceroute stop reason with its stop data; `ipv6` tells whether the
+    /// traceroute is over IPv6, which determines the meaning of ICMP codes. The stop data
+    /// of an ICMP stop is the message type only, so the code is left to the caller; see
+    /// [`crate::Traceroute::stop_cause`].
+    pub fn from_trace(reason: TraceStopReason, data: u8, ipv6: bool) -> Self {
+        match reason {
+            TraceStopReason::None => StopCause::None,
+            TraceStopReason::Completed => StopCause::Completed,
+            TraceStopReason::Unreach => {
+                let icmp_type = if ipv6 { 1 } else { 3 };
+                match IcmpKind::new(icmp_type, data, ipv6) {
+                    IcmpKind::DestUnreach { reason } => StopCause::Unreachable(reason),
+                    // Fragmentation needed (ICMPv4 code 4).
+                    _ => StopCause::Unreachable(UnreachableReason::Other(data)),
+                }
+            }
+            TraceStopReason::ICMP => StopCause::Icmp {
+                icmp_type: data,
+                icmp_code: None,
+            },
+            TraceStopReason::Loop => StopCause::Loop,
+            TraceStopReason::GapLimit => StopCause::GapLimit,
+            TraceStopReason::Error => StopCause::SendError(data),
+            TraceStopReason::HopLimit => StopCause::HopLimit,
+            TraceStopReason::GSS => StopCause::GlobalStopSet,
+            TraceStopReason::Halted => StopCause::Halted,
+        }
+    }
+
+    /// Combines a ping stop reason with its stop data.
+    pub fn from_ping(reason: PingStopReason, data: u8) -> Self {
+        match reason {
+            PingStopReason::None => StopCause::None,
+            PingStopReason::Completed => StopCause::Completed,
+            PingStopReason::Error => StopCause::SendError(data),
+            PingStopReason::Halted => StopCause::Halted,
+        }
+    }
+}
+
+impl Display for StopCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopCause::None => write!(f, "none"),
+            StopCause::Completed => write!(f, "completed"),
+            StopCause::Unreachable(reason) => {
+                let reason = match reason {
+                    UnreachableReason::Network => "network",
+                    UnreachableReason::Host => "host",
+                    UnreachableReason::Protocol => "protocol",
+                    UnreachableReason::Port => "port",
+                    UnreachableReason::SourceRouteFailed => "source route failed",
+                    UnreachableReason::AdministrativelyProhibited => "administratively prohibited",
+                    UnreachableReason::BeyondScope => "beyond scope",
+                    UnreachableReason::Other(code) => {
+                        return write!(f, "unreachable (code {})", code)
+                    }
+                };
+                write!(f, "unreachable ({})", reason)
+            }
+            StopCause::Icmp {
+                icmp_type,
+                icmp_code: Some(code),
+            } => write!(f, "icmp (type {}, code {})", icmp_type, code),
+            StopCause::Icmp { icmp_type, .. } => write!(f, "icmp (type {})", icmp_type),
+            StopCause::Loop => write!(f, "loop"),
+            StopCause::GapLimit => write!(f, "gap limit"),
+            StopCause::SendError(errno) => write!(f, "error (errno {})", errno),
+            StopCause::HopLimit => write!(f, "hop limit"),
+            StopCause::GlobalStopSet => write!(f, "global stop set"),
+            StopCause::Halted => write!(f, "halted"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Address, Object, PingStopReason, StopCause, TraceStopReason, UnreachableReason};
+
+    #[test]
+    fn trace_and_ping() {
+        let cause = StopCause::from_trace(TraceStopReason::Unreach, 13, false);
+        assert_eq!(
+            cause,
+            StopCause::Unreachable(UnreachableReason::AdministrativelyProhibited)
+        );
+        assert_eq!(
+            cause.to_string(),
+            "unreachable (administratively prohibited)"
+        );
+        assert_eq!(
+            StopCause::from_trace(TraceStopReason::Unreach, 1, true),
+            StopCause::Unreachable(UnreachableReason::AdministrativelyProhibited)
+        );
+        assert_eq!(
+            StopCause::from_trace(TraceStopReason::Error, 13, false).to_string(),
+            "error (errno 13)"
+        );
+        assert_eq!(
+            StopCause::from_ping(PingStopReason::Error, 1),
+            StopCause::SendError(1)
+        );
+
+        let data = include_bytes!("../data/trace_google_dns_v4_default.warts");
+        for object in Object::all_from_bytes(data) {
+            if let Object::Traceroute(mut trace) = object {
+                assert_eq!(trace.stop_cause(), Some(StopCause::Completed));
+                // Pretend the traceroute stopped on a parameter problem from the last hop.
+                let hop = trace.hops.last_mut().unwrap();
+                hop.icmp_type = Some(12);
+                hop.icmp_code = Some(1);
+                trace.stop_reason = Some(TraceStopReason::ICMP);
+                trace.stop_data = Some(12);
+                let cause = trace.stop_cause().unwrap();
+                assert_eq!(
+                    cause,
+                    StopCause::Icmp {
+                        icmp_type: 12,
+                        icmp_code: Some(1)
+                    }
+                );
+                assert_eq!(cause.to_string(), "icmp (type 12, code 1)");
+                // Only the stop hop gives the code.
+                trace.hops.last_mut().unwrap().icmp_type = Some(11);
+                let index = trace.hops.len() - 2;
+                trace.hops[index].icmp_type = Some(12);
+                assert_eq!(
+                    trace.stop_cause(),
+                    Some(StopCause::Icmp {
+                        icmp_type: 12,
+                        icmp_code: None
+                    })
+                );
+            }
+        }
+        let data = include_bytes!("../data/trace_google_dns_v6_default.warts");
+        for mut object in Object::all_from_bytes(data) {
+            object.dereference();
+            if let Object::Traceroute(mut trace) = object {
+                assert_eq!(trace.stop_cause(), Some(StopCause::Completed));
+                // ICMPv6 code 1 is administratively prohibited, not host unreachable.
+                trace.stop_reason = Some(TraceStopReason::Unreach);
+                trace.stop_data = Some(1);
+                assert_eq!(
+                    trace.stop_cause(),
+                    Some(StopCause::Unreachable(
+                        UnreachableReason::AdministrativelyProhibited
+                    ))
+                );
+                // The family is unknown until the destination is dereferenced.
+                trace.dst_addr = Some(Address::Reference(1));
+                assert_eq!(trace.stop_cause(), None);
+            }
+        }
+        let data = include_bytes!("../data/ping_google_dns_v4_default.warts");
+        for object in Object::all_from_bytes(data) {
+            if let Object::Ping(ping) = object {
+                assert_eq!(ping.stop_cause(), Some(StopCause::Completed));
+            }
+        }
+    }
+}
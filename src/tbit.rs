@@ -0,0 +1,826 @@
+use crate::object::AddressSlot;
+use crate::{parse_tcp_packet, Address, Flags, IpHeader, TcpHeader, Timeval, WartsSized};
+use deku::prelude::*;
+use std::ffi::CString;
+
+/// Type of TCP behaviour inference test.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum TbitType {
+    /// Path MTU discovery black hole detection.
+    #[deku(id = "0x01")]
+    PMTUD,
+    /// Explicit congestion notification support.
+    #[deku(id = "0x02")]
+    ECN,
+    /// Response to a TCP packet without flags.
+    #[deku(id = "0x03")]
+    Null,
+    /// Selective acknowledgment support, as a receiver.
+    #[deku(id = "0x04")]
+    SACKReceiver,
+    /// Initial congestion window.
+    #[deku(id = "0x05")]
+    ICW,
+    /// Appropriate byte counting.
+    #[deku(id = "0x06")]
+    ABC,
+    /// Response to blind data injection.
+    #[deku(id = "0x07")]
+    BlindData,
+    /// Response to blind RST injection.
+    #[deku(id = "0x08")]
+    BlindRST,
+    /// Response to blind SYN injection.
+    #[deku(id = "0x09")]
+    BlindSYN,
+    /// Response to blind FIN injection.
+    #[deku(id = "0x0a")]
+    BlindFIN,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// Application protocol used by a TCP behaviour inference test.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum TbitAppProto {
+    #[deku(id = "0x01")]
+    HTTP,
+    #[deku(id = "0x02")]
+    SMTP,
+    #[deku(id = "0x03")]
+    DNS,
+    #[deku(id = "0x04")]
+    FTP,
+    #[deku(id = "0x05")]
+    BGP,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// Outcome of a TCP behaviour inference test.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u16")]
+pub enum TbitResult {
+    #[deku(id = "0")]
+    None,
+    #[deku(id = "1")]
+    TcpNoConnection,
+    #[deku(id = "2")]
+    TcpRst,
+    #[deku(id = "3")]
+    TcpError,
+    #[deku(id = "4")]
+    Error,
+    #[deku(id = "5")]
+    Aborted,
+    #[deku(id = "6")]
+    TcpNoConnectionRst,
+    #[deku(id = "7")]
+    Halted,
+    #[deku(id = "8")]
+    TcpBadOption,
+    #[deku(id = "9")]
+    TcpFin,
+    #[deku(id = "10")]
+    TcpZeroWindow,
+    #[deku(id = "20")]
+    PmtudNoAck,
+    #[deku(id = "21")]
+    PmtudNoData,
+    #[deku(id = "22")]
+    PmtudTooSmall,
+    #[deku(id = "23")]
+    PmtudNoDF,
+    #[deku(id = "24")]
+    PmtudFail,
+    #[deku(id = "25")]
+    PmtudSuccess,
+    #[deku(id = "26")]
+    PmtudClearDF,
+    #[deku(id = "30")]
+    EcnSuccess,
+    #[deku(id = "31")]
+    EcnIncapable,
+    #[deku(id = "32")]
+    EcnBadSynAck,
+    #[deku(id = "33")]
+    EcnNoEce,
+    #[deku(id = "34")]
+    EcnNoAck,
+    #[deku(id = "35")]
+    EcnNoData,
+    #[deku(id = "40")]
+    NullSuccess,
+    #[deku(id = "41")]
+    NullNoData,
+    #[deku(id = "50")]
+    SackIncapable,
+    #[deku(id = "51")]
+    SackReceiverSuccess,
+    #[deku(id = "52")]
+    SackReceiverShifted,
+    #[deku(id = "53")]
+    SackReceiverTimeout,
+    #[deku(id = "54")]
+    SackReceiverNoSack,
+    #[deku(id = "60")]
+    IcwSuccess,
+    #[deku(id = "61")]
+    IcwTooShort,
+    #[deku(id = "70")]
+    AbcSuccess,
+    #[deku(id = "71")]
+    AbcTooShort,
+    #[deku(id = "72")]
+    AbcBadIcw,
+    #[deku(id = "80")]
+    BlindAccepted,
+    #[deku(id = "81")]
+    BlindChallenge,
+    #[deku(id = "82")]
+    BlindIgnored,
+    #[deku(id = "83")]
+    BlindRst,
+    #[deku(id = "84")]
+    BlindSynNew,
+    /// A result code unknown to this library.
+    #[deku(id_pat = "_")]
+    Unknown(u16),
+}
+
+/// Direction of a packet recorded during a TCP behaviour inference test.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum TbitPacketDirection {
+    /// Packet sent by scamper.
+    #[deku(id = "0x01")]
+    Transmitted,
+    /// Packet received by scamper.
+    #[deku(id = "0x02")]
+    Received,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// A TCP behaviour inference test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct Tbit {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    /// Parameter flags, kept with `serde-skip-wire` since they number the unknown parameters.
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the list the test was scheduled from, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// ID of the cycle during which the test ran, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// Arbitrary user ID attached to the test, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Local address of the TCP connection, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub src_addr: Option<Address>,
+    /// Address of the remote host whose TCP stack is tested, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub dst_addr: Option<Address>,
+    /// Source port, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub src_port: Option<u16>,
+    /// Destination port, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub dst_port: Option<u16>,
+    /// Time test commenced, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub start_time: Option<Timeval>,
+    /// Outcome of the test, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub result: Option<TbitResult>,
+    /// Type of test, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub tbit_type: Option<TbitType>,
+    /// Application protocol, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub app_proto: Option<TbitAppProto>,
+    /// Maximum segment size advertised by the client, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    pub client_mss: Option<u16>,
+    /// Maximum segment size advertised by the server, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)")]
+    pub server_mss: Option<u16>,
+    /// Number of SYN retransmissions allowed, included if flag 14 is set.
+    #[deku(cond = "flags.get(14)")]
+    pub syn_retx: Option<u8>,
+    /// Number of data retransmissions allowed, included if flag 15 is set.
+    #[deku(cond = "flags.get(15)")]
+    pub data_retx: Option<u8>,
+    /// Number of packets recorded (16-bit, deprecated), included if flag 16 is set.
+    #[deku(cond = "flags.get(16)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub packet_count_u16: Option<u16>,
+    /// Number of packets recorded, included if flag 17 is set.
+    #[deku(cond = "flags.get(17)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub packet_count: Option<u32>,
+    /// Test options, included if flag 18 is set.
+    #[deku(cond = "flags.get(18)")]
+    pub options: Option<u32>,
+    /// Window scale advertised by the client, included if flag 19 is set.
+    #[deku(cond = "flags.get(19)")]
+    pub client_wscale: Option<u8>,
+    /// IP TTL of the packets sent, included if flag 20 is set.
+    #[deku(cond = "flags.get(20)")]
+    pub ttl: Option<u8>,
+    /// Raw parameters following the TTL (TCP fast open cookie and later additions),
+    /// which are not decoded but kept so that the test can be written back.
+    #[deku(
+        cond = "flags.any()",
+        count = "(param_length.unwrap_or(0) as usize + 4 + flags.warts_size() + 2).saturating_sub(deku::byte_offset)"
+    )]
+    pub unknown_params: Vec<u8>,
+    /// Test-specific parameters.
+    #[deku(ctx = "*tbit_type")]
+    pub data: TbitData,
+    /// Application-specific parameters.
+    #[deku(ctx = "*app_proto")]
+    pub app: TbitApp,
+    /// Packets sent and received during the test.
+    #[deku(count = "packet_count.or(packet_count_u16.map(u32::from)).unwrap_or(0)")]
+    pub packets: Vec<TbitPacket>,
+}
+
+/// Test-specific parameters of a TCP behaviour inference test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(
+    ctx = "endian: deku::ctx::Endian, tbit_type: Option<TbitType>",
+    id = "tbit_type",
+    endian = "endian"
+)]
+pub enum TbitData {
+    #[deku(id = "Some(TbitType::PMTUD)")]
+    PMTUD(TbitPmtud),
+    #[deku(id = "Some(TbitType::Null)")]
+    Null(TbitNull),
+    #[deku(id = "Some(TbitType::ICW)")]
+    ICW(TbitIcw),
+    #[deku(
+        id_pat = "Some(TbitType::BlindData | TbitType::BlindRST | TbitType::BlindSYN | TbitType::BlindFIN)"
+    )]
+    Blind(TbitBlind),
+    /// No parameters, for the other tests or if the type is not specified.
+    #[deku(id_pat = "_")]
+    None,
+}
+
+/// Application-specific parameters of a TCP behaviour inference test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(
+    ctx = "endian: deku::ctx::Endian, app_proto: Option<TbitAppProto>",
+    id = "app_proto",
+    endian = "endian"
+)]
+pub enum TbitApp {
+    #[deku(id = "Some(TbitAppProto::HTTP)")]
+    HTTP(TbitAppHttp),
+    #[deku(id = "Some(TbitAppProto::BGP)")]
+    BGP(TbitAppBgp),
+    /// No parameters, for the other protocols or if the protocol is not specified.
+    #[deku(id_pat = "_")]
+    None,
+}
+
+/// Parameters of a PMTUD test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitPmtud {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// MTU advertised in the packet too big messages, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub mtu: Option<u16>,
+    /// Number of packet too big retransmissions, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub ptb_retx: Option<u8>,
+    /// PMTUD options, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub options: Option<u8>,
+    /// Source address of the packet too big messages, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub ptb_src: Option<Address>,
+}
+
+/// Parameters of a null test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitNull {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Null test options, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub options: Option<u16>,
+    /// Null test results, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub results: Option<u16>,
+}
+
+/// Parameters of an initial congestion window test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitIcw {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Sequence number of the first data segment, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub start_seq: Option<u32>,
+}
+
+/// Parameters of a blind injection test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitBlind {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Offset of the injected sequence or acknowledgment number, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub offset: Option<i32>,
+    /// Number of retransmissions, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub retx: Option<u8>,
+}
+
+/// HTTP parameters of a TCP behaviour inference test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitAppHttp {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Host requested, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub host: Option<CString>,
+    /// File requested, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub file: Option<CString>,
+    /// HTTP or HTTPS, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub http_type: Option<u8>,
+}
+
+/// BGP parameters of a TCP behaviour inference test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitAppBgp {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// AS number advertised in the OPEN message, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub asn: Option<u32>,
+}
+
+/// A packet sent or received during a TCP behaviour inference test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TbitPacket {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Direction of the packet, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub direction: Option<TbitPacketDirection>,
+    /// Time the packet was sent or received, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub timestamp: Option<Timeval>,
+    /// Length of the packet, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub data_length: Option<u16>,
+    /// Raw IP packet, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)", count = "data_length.unwrap_or(0)")]
+    pub data: Option<Vec<u8>>,
+}
+
+impl Tbit {
+    /// Source and destination addresses, then the source of the packet too big messages.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        let mut slots = vec![
+            AddressSlot::Optional(&mut self.src_addr, None),
+            AddressSlot::Optional(&mut self.dst_addr, None),
+        ];
+        if let TbitData::PMTUD(pmtud) = &mut self.data {
+            slots.push(AddressSlot::Optional(&mut pmtud.ptb_src, None));
+        }
+        slots
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.packet_count_u16 = None;
+        self.packet_count = Some(self.packets.len() as u32);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.src_addr);
+        push_flag!(flags, param_length, 5, self.dst_addr);
+        push_flag!(flags, param_length, 6, self.src_port);
+        push_flag!(flags, param_length, 7, self.dst_port);
+        push_flag!(flags, param_length, 8, self.start_time);
+        push_flag!(flags, param_length, 9, self.result);
+        push_flag!(flags, param_length, 10, self.tbit_type);
+        push_flag!(flags, param_length, 11, self.app_proto);
+        push_flag!(flags, param_length, 12, self.client_mss);
+        push_flag!(flags, param_length, 13, self.server_mss);
+        push_flag!(flags, param_length, 14, self.syn_retx);
+        push_flag!(flags, param_length, 15, self.data_retx);
+        push_flag!(flags, param_length, 16, self.packet_count_u16);
+        push_flag!(flags, param_length, 17, self.packet_count);
+        push_flag!(flags, param_length, 18, self.options);
+        push_flag!(flags, param_length, 19, self.client_wscale);
+        push_flag!(flags, param_length, 20, self.ttl);
+        if !self.unknown_params.is_empty() {
+            flags.extend((21..64).filter(|&i| self.flags.get(i as usize)));
+            param_length += self.unknown_params.len();
+        }
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        let packets_size: usize = self.packets.iter().map(|packet| packet.warts_size()).sum();
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.data.warts_size()
+            + self.app.warts_size()
+            + packets_size) as u32;
+        self
+    }
+}
+
+impl_measurement!(Tbit);
+
+impl TbitData {
+    pub fn finalize(self) -> Self {
+        match self {
+            TbitData::PMTUD(x) => TbitData::PMTUD(x.finalize()),
+            TbitData::Null(x) => TbitData::Null(x.finalize()),
+            TbitData::ICW(x) => TbitData::ICW(x.finalize()),
+            TbitData::Blind(x) => TbitData::Blind(x.finalize()),
+            TbitData::None => TbitData::None,
+        }
+    }
+}
+
+impl TbitApp {
+    pub fn finalize(self) -> Self {
+        match self {
+            TbitApp::HTTP(x) => TbitApp::HTTP(x.finalize()),
+            TbitApp::BGP(x) => TbitApp::BGP(x.finalize()),
+            TbitApp::None => TbitApp::None,
+        }
+    }
+}
+
+impl TbitPmtud {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.mtu);
+        push_flag!(flags, param_length, 2, self.ptb_retx);
+        push_flag!(flags, param_length, 3, self.options);
+        push_flag!(flags, param_length, 4, self.ptb_src);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TbitNull {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.options);
+        push_flag!(flags, param_length, 2, self.results);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TbitIcw {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.start_seq);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TbitBlind {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.offset);
+        push_flag!(flags, param_length, 2, self.retx);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TbitAppHttp {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.host);
+        push_flag!(flags, param_length, 2, self.file);
+        push_flag!(flags, param_length, 3, self.http_type);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TbitAppBgp {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.asn);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TbitPacket {
+    /// Decodes the IP header of the packet.
+    pub fn ip_header(&self) -> Option<IpHeader> {
+        IpHeader::parse(self.data.as_deref()?)
+    }
+
+    /// Decodes the TCP header of the packet, if it is a TCP segment.
+    pub fn tcp_header(&self) -> Option<TcpHeader> {
+        parse_tcp_packet(self.data.as_deref()?).map(|(_, tcp, _)| tcp)
+    }
+
+    /// Returns the TCP payload of the packet, if it is a TCP segment.
+    pub fn tcp_payload(&self) -> Option<&[u8]> {
+        parse_tcp_packet(self.data.as_deref()?).map(|(_, _, payload)| payload)
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.data_length = self.data.as_ref().map(|data| data.len() as u16);
+        push_flag!(flags, param_length, 1, self.direction);
+        push_flag!(flags, param_length, 2, self.timestamp);
+        push_flag!(flags, param_length, 3, self.data_length);
+        push_flag!(flags, param_length, 4, self.data);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for TbitType {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for TbitAppProto {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for TbitResult {
+    fn warts_size(&self) -> usize {
+        2
+    }
+}
+
+impl WartsSized for TbitPacketDirection {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+fn params_size(flags: &Flags, param_length: &Option<u16>) -> usize {
+    flags.warts_size() + param_length.warts_size() + param_length.unwrap_or(0) as usize
+}
+
+impl WartsSized for TbitData {
+    fn warts_size(&self) -> usize {
+        match self {
+            TbitData::PMTUD(x) => params_size(&x.flags, &x.param_length),
+            TbitData::Null(x) => params_size(&x.flags, &x.param_length),
+            TbitData::ICW(x) => params_size(&x.flags, &x.param_length),
+            TbitData::Blind(x) => params_size(&x.flags, &x.param_length),
+            TbitData::None => 0,
+        }
+    }
+}
+
+impl WartsSized for TbitApp {
+    fn warts_size(&self) -> usize {
+        match self {
+            TbitApp::HTTP(x) => params_size(&x.flags, &x.param_length),
+            TbitApp::BGP(x) => params_size(&x.flags, &x.param_length),
+            TbitApp::None => 0,
+        }
+    }
+}
+
+impl WartsSized for TbitPacket {
+    fn warts_size(&self) -> usize {
+        params_size(&self.flags, &self.param_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, Object, Tbit, TbitApp, TbitAppHttp, TbitAppProto, TbitData, TbitPacket,
+        TbitPacketDirection, TbitPmtud, TbitResult, TbitType, TcpHeader, Timeval,
+    };
+    use deku::bitvec::{BitVec, BitView};
+    use deku::prelude::*;
+    use std::ffi::CString;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    // IPv4 + TCP SYN from 192.0.2.1:40000 to 198.51.100.1:80, with an MSS option.
+    const SYN: [u8; 44] = [
+        0x45, 0x00, 0x00, 0x2c, 0x12, 0x34, 0x40, 0x00, 0x40, 0x06, 0x00, 0x00, 0xc0, 0x00, 0x02,
+        0x01, 0xc6, 0x33, 0x64, 0x01, 0x9c, 0x40, 0x00, 0x50, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x60, 0x02, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x02, 0x04, 0x05, 0xb4,
+    ];
+
+    #[test]
+    fn pmtud_round_trip() {
+        let packet = TbitPacket {
+            flags: Default::default(),
+            param_length: None,
+            direction: Some(TbitPacketDirection::Transmitted),
+            timestamp: Some(Timeval::new(1644339038, 0)),
+            data_length: None,
+            data: Some(SYN.to_vec()),
+        }
+        .finalize();
+        let tbit = Tbit {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            dst_addr: Some(Address::from(Ipv4Addr::new(198, 51, 100, 1))),
+            src_port: Some(40000),
+            dst_port: Some(80),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            result: Some(TbitResult::PmtudSuccess),
+            tbit_type: Some(TbitType::PMTUD),
+            app_proto: Some(TbitAppProto::HTTP),
+            client_mss: Some(1460),
+            server_mss: None,
+            syn_retx: None,
+            data_retx: None,
+            packet_count_u16: None,
+            packet_count: None,
+            options: None,
+            client_wscale: None,
+            ttl: None,
+            unknown_params: vec![],
+            data: TbitData::PMTUD(TbitPmtud {
+                flags: Default::default(),
+                param_length: None,
+                mtu: Some(1280),
+                ptb_retx: Some(4),
+                options: None,
+                ptb_src: None,
+            })
+            .finalize(),
+            app: TbitApp::HTTP(TbitAppHttp {
+                flags: Default::default(),
+                param_length: None,
+                host: Some(CString::new("www.example.org").unwrap()),
+                file: Some(CString::new("/").unwrap()),
+                http_type: None,
+            })
+            .finalize(),
+            packets: vec![packet],
+        }
+        .finalize();
+        let bytes = Object::Tbit(tbit.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + tbit.length as usize);
+        let objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::Tbit(tbit.clone())]);
+
+        let packet = &tbit.packets[0];
+        let ip = packet.ip_header().unwrap();
+        assert_eq!(ip.src_addr, IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(ip.ip_id, Some(0x1234));
+        let tcp = packet.tcp_header().unwrap();
+        assert_eq!((tcp.src_port, tcp.dst_port), (40000, 80));
+        assert!(tcp.has_flags(TcpHeader::SYN));
+        assert!(!tcp.has_flags(TcpHeader::ACK));
+        assert_eq!(tcp.options, vec![0x02, 0x04, 0x05, 0xb4]);
+        assert_eq!(packet.tcp_payload(), Some(&[][..]));
+    }
+
+    #[test]
+    fn unknown_result() {
+        let input = [0x04, 0xd2];
+        let (_, result) = TbitResult::read(input.view_bits(), deku::ctx::Endian::Big).unwrap();
+        assert_eq!(result, TbitResult::Unknown(1234));
+        let mut output = BitVec::new();
+        result.write(&mut output, deku::ctx::Endian::Big).unwrap();
+        assert_eq!(output.into_vec(), input);
+    }
+
+    #[test]
+    fn unknown_params_and_packet_data() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x12, 0x05, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x1e,
+            // Flags 17 (packet count), 20 (TTL) and 21 (unknown), 8 bytes of parameters.
+            0x80, 0x80, 0x64, 0x00, 0x08,
+            0x00, 0x00, 0x00, 0x01, 0x40, 0xaa, 0xbb, 0xcc,
+            // Packet with flags 2 (time), 3 (length) and 4 (data), 14 bytes of parameters.
+            0x0e, 0x00, 0x0e,
+            0x62, 0x02, 0x46, 0x5e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04,
+        ];
+        let objects = Object::all_from_bytes(&bytes);
+        let Object::Tbit(tbit) = &objects[0] else {
+            panic!("expected a tbit test");
+        };
+        assert_eq!(tbit.ttl, Some(64));
+        assert_eq!(tbit.unknown_params, vec![0xaa, 0xbb, 0xcc]);
+        assert_eq!(tbit.packets[0].timestamp, Some(Timeval::new(1644316254, 0)));
+        assert_eq!(tbit.packets[0].data, Some(vec![0x01, 0x02, 0x03, 0x04]));
+        assert_eq!(objects[0].to_bytes().unwrap(), bytes);
+        let tbit = Object::Tbit(tbit.clone().finalize());
+        assert_eq!(tbit.to_bytes().unwrap(), bytes);
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&tbit).unwrap();
+            let Object::Tbit(tbit) = serde_json::from_str(&json).unwrap() else {
+                panic!("expected a tbit test");
+            };
+            let packet = tbit.packets[0].clone().finalize();
+            let tbit = Tbit {
+                packets: vec![packet],
+                ..tbit
+            };
+            assert_eq!(Object::Tbit(tbit.finalize()).to_bytes().unwrap(), bytes);
+        }
+    }
+}
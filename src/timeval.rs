@@ -1,37 +1,329 @@
 use crate::WartsSized;
-use chrono::NaiveDateTime;
 use deku::prelude::*;
 use std::mem::size_of_val;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// A timestamp with a microsecond resolution.
+///
+/// Conversions are provided from and to [`SystemTime`] and [`Duration`] (since Unix epoch),
+/// as well as to `chrono` and `time` types when the corresponding features are enabled.
+/// Conversions into a timeval can fall outside of its range, from 1970 to 2106, and are
+/// fallible; the exception is `From<NaiveDateTime>`, kept for compatibility, which clamps.
 /// ```
+/// use std::time::Duration;
+/// use warts::Timeval;
+/// let tx = Timeval::new(1612829505, 999_000);
+/// let rx = tx.checked_add(Duration::from_micros(2_500)).unwrap();
+/// assert_eq!(rx, Timeval::new(1612829506, 1_500));
+/// assert_eq!(rx.checked_duration_since(tx), Some(Duration::from_micros(2_500)));
+/// assert!(tx < rx);
+/// ```
+/// ```
+/// # #[cfg(feature = "chrono")] {
 /// use chrono::{NaiveDate, NaiveDateTime};
 /// use warts::Timeval;
 /// // Rust to Warts:
-/// let tv = Timeval::from(NaiveDate::from_ymd(2021, 2, 9).and_hms(0, 11, 45));
+/// let tv = Timeval::from(NaiveDate::from_ymd_opt(2021, 2, 9).unwrap().and_hms_opt(0, 11, 45).unwrap());
 /// // Warts to Rust:
 /// let dt = NaiveDateTime::from(tv);
+/// # }
 /// ```
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, DekuRead, DekuWrite)]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct Timeval {
     pub seconds: u32,
     pub microseconds: u32,
 }
 
-impl From<Timeval> for NaiveDateTime {
+impl Timeval {
+    pub fn new(seconds: u32, microseconds: u32) -> Self {
+        Timeval {
+            seconds,
+            microseconds,
+        }
+    }
+
+    /// Returns the amount of time elapsed from `earlier` to `self`,
+    /// or `None` if `earlier` is later than `self`.
+    pub fn checked_duration_since(&self, earlier: Timeval) -> Option<Duration> {
+        Duration::from(*self).checked_sub(Duration::from(earlier))
+    }
+
+    /// Returns `self + duration`, or `None` if the result does not fit in a timeval.
+    pub fn checked_add(&self, duration: Duration) -> Option<Timeval> {
+        let sum = Duration::from(*self).checked_add(duration)?;
+        Timeval::try_from(sum).ok()
+    }
+
+    /// Returns `self - duration`, or `None` if the result is before Unix epoch.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Timeval> {
+        let difference = Duration::from(*self).checked_sub(duration)?;
+        Timeval::try_from(difference).ok()
+    }
+}
+
+/// The error returned when a time cannot be represented as a [`Timeval`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimevalRangeError;
+
+impl std::fmt::Display for TimevalRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("time out of the range of a timeval")
+    }
+}
+
+impl std::error::Error for TimevalRangeError {}
+
+impl From<Timeval> for Duration {
+    /// Returns the time elapsed since Unix epoch.
     fn from(x: Timeval) -> Self {
-        NaiveDateTime::from_timestamp_opt(x.seconds as i64, x.microseconds * 1000).unwrap()
+        Duration::from_secs(x.seconds as u64) + Duration::from_micros(x.microseconds as u64)
     }
 }
 
-impl From<NaiveDateTime> for Timeval {
-    fn from(x: NaiveDateTime) -> Self {
-        Timeval {
-            seconds: x.timestamp() as u32,
+impl TryFrom<Duration> for Timeval {
+    type Error = TimevalRangeError;
+
+    /// Builds a timestamp from the time elapsed since Unix epoch,
+    /// which must not exceed `u32::MAX` seconds.
+    fn try_from(x: Duration) -> Result<Self, Self::Error> {
+        Ok(Timeval {
+            seconds: u32::try_from(x.as_secs()).map_err(|_| TimevalRangeError)?,
+            microseconds: x.subsec_micros(),
+        })
+    }
+}
+
+impl From<Timeval> for SystemTime {
+    fn from(x: Timeval) -> Self {
+        UNIX_EPOCH + Duration::from(x)
+    }
+}
+
+impl TryFrom<SystemTime> for Timeval {
+    type Error = TimevalRangeError;
+
+    /// Fails for times before Unix epoch or after 2106-02-07.
+    fn try_from(x: SystemTime) -> Result<Self, Self::Error> {
+        let elapsed = x
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| TimevalRangeError)?;
+        Timeval::try_from(elapsed)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timeval> for chrono::DateTime<chrono::Utc> {
+    fn from(x: Timeval) -> Self {
+        let elapsed = Duration::from(x);
+        chrono::DateTime::from_timestamp(elapsed.as_secs() as i64, elapsed.subsec_nanos())
+            .expect("a timeval is within the range of chrono")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for Timeval {
+    type Error = TimevalRangeError;
+
+    /// Fails for times before Unix epoch or after 2106-02-07.
+    fn try_from(x: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        Ok(Timeval {
+            seconds: u32::try_from(x.timestamp()).map_err(|_| TimevalRangeError)?,
             microseconds: x.timestamp_subsec_micros(),
+        })
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Timeval> for chrono::NaiveDateTime {
+    fn from(x: Timeval) -> Self {
+        chrono::DateTime::<chrono::Utc>::from(x).naive_utc()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDateTime> for Timeval {
+    /// Clamps times before Unix epoch or after 2106-02-07 to the range of a timeval;
+    /// use `Timeval::try_from(x.and_utc())` to reject them instead.
+    fn from(x: chrono::NaiveDateTime) -> Self {
+        let x = x.and_utc();
+        Timeval::try_from(x).unwrap_or(if x.timestamp() < 0 {
+            Timeval::new(0, 0)
+        } else {
+            Timeval::new(u32::MAX, 999_999)
+        })
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<Timeval> for time::OffsetDateTime {
+    fn from(x: Timeval) -> Self {
+        time::OffsetDateTime::UNIX_EPOCH + Duration::from(x)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for Timeval {
+    type Error = TimevalRangeError;
+
+    /// Fails for times before Unix epoch or after 2106-02-07.
+    fn try_from(x: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        Ok(Timeval {
+            seconds: u32::try_from(x.unix_timestamp()).map_err(|_| TimevalRangeError)?,
+            microseconds: x.microsecond(),
+        })
+    }
+}
+
+/// Timestamps are represented as RFC 3339 strings in UTC with a microsecond precision,
+/// e.g. `2021-02-09T00:11:45.123456Z`. Microseconds beyond a second are carried over to
+/// the seconds, so a timeval that is not normalized deserializes to its normalized form.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Timeval {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let elapsed = Duration::from(*self);
+        let (seconds, microseconds) = (elapsed.as_secs(), elapsed.subsec_micros());
+        let (date, time) = (seconds / 86400, seconds % 86400);
+        let (year, month, day) = civil_from_days(date as i64);
+        serializer.collect_str(&format_args!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+            year,
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+            microseconds
+        ))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Timeval {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_rfc3339(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid RFC 3339 timestamp: {}", s)))
+    }
+}
+
+/// Returns the (year, month, day) corresponding to a number of days since Unix epoch.
+/// From <http://howardhinnant.github.io/date_algorithms.html>.
+#[cfg(feature = "serde")]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Returns the number of days since Unix epoch corresponding to a (year, month, day).
+#[cfg(feature = "serde")]
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Returns the number of days in a month of the proleptic Gregorian calendar.
+#[cfg(feature = "serde")]
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses an RFC 3339 timestamp such as `2021-02-09T00:11:45.123456+01:00`, rejecting
+/// out-of-range dates, times and offsets. A leap second (`:60`) is folded into the
+/// preceding second, as timevals cannot represent it.
+#[cfg(feature = "serde")]
+fn parse_rfc3339(s: &str) -> Option<Timeval> {
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = s.get(range)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+    let separators = s.as_bytes();
+    if separators.len() < 20
+        || separators[4] != b'-'
+        || separators[7] != b'-'
+        || !matches!(separators[10], b'T' | b't' | b' ')
+        || separators[13] != b':'
+        || separators[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+    let mut rest = &s[19..];
+    let mut microseconds = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
         }
+        let digits = &fraction[..len.min(6)];
+        microseconds = digits.parse::<u32>().ok()? * 10u32.pow(6 - digits.len() as u32);
+        rest = &fraction[len..];
     }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let offset = &rest[1..];
+            let digits = |range: std::ops::Range<usize>| -> Option<i64> {
+                let digits = offset.get(range)?;
+                digits
+                    .bytes()
+                    .all(|b| b.is_ascii_digit())
+                    .then(|| digits.parse().ok())?
+            };
+            if offset.len() != 5 || offset.as_bytes()[2] != b':' {
+                return None;
+            }
+            let (hours, minutes) = (digits(0..2)?, digits(3..5)?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+    let seconds = days_from_civil(year, month as u32, day as u32) * 86400
+        + hour * 3600
+        + minute * 60
+        + second.min(59)
+        - offset;
+    Some(Timeval {
+        seconds: u32::try_from(seconds).ok()?,
+        microseconds,
+    })
 }
 
 impl WartsSized for Timeval {
@@ -43,14 +335,107 @@ impl WartsSized for Timeval {
 #[cfg(test)]
 mod tests {
     use crate::Timeval;
-    use chrono::{NaiveDate, NaiveDateTime};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     #[test]
+    #[cfg(feature = "chrono")]
     fn from_date_time() {
+        use chrono::{NaiveDate, NaiveDateTime};
         let dt = NaiveDate::from_ymd_opt(2021, 2, 9)
             .unwrap()
             .and_hms_opt(0, 11, 45)
             .unwrap();
-        assert_eq!(NaiveDateTime::from(Timeval::from(dt)), dt);
+        let tv = Timeval::from(dt);
+        assert_eq!(NaiveDateTime::from(tv), dt);
+        let before_epoch = NaiveDate::from_ymd_opt(1969, 12, 31)
+            .unwrap()
+            .and_time(dt.time());
+        assert_eq!(Timeval::from(before_epoch), Timeval::new(0, 0));
+        assert!(Timeval::try_from(before_epoch.and_utc()).is_err());
+        let dt = dt.and_utc();
+        let tv = Timeval::try_from(dt).unwrap();
+        assert_eq!(chrono::DateTime::from(tv), dt);
+        let last = Timeval::new(u32::MAX, 999_999);
+        assert_eq!(Timeval::from(NaiveDateTime::from(last)), last);
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn from_offset_date_time() {
+        let dt =
+            time::OffsetDateTime::from_unix_timestamp_nanos(1_612_829_505_123_456_000).unwrap();
+        let tv = Timeval::try_from(dt).unwrap();
+        assert_eq!(time::OffsetDateTime::from(tv), dt);
+        let after_2106 = time::OffsetDateTime::from_unix_timestamp(1 << 32).unwrap();
+        assert!(Timeval::try_from(after_2106).is_err());
+    }
+
+    #[test]
+    fn from_system_time() {
+        let st = UNIX_EPOCH + Duration::from_micros(1_612_829_505_123_456);
+        let tv = Timeval::try_from(st).unwrap();
+        assert_eq!(tv, Timeval::new(1612829505, 123456));
+        assert_eq!(SystemTime::from(tv), st);
+        assert!(Timeval::try_from(UNIX_EPOCH - Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_rfc3339() {
+        let tv = Timeval::new(1612829505, 123456);
+        let json = serde_json::to_string(&tv).unwrap();
+        assert_eq!(json, r#""2021-02-09T00:11:45.123456Z""#);
+        assert_eq!(serde_json::from_str::<Timeval>(&json).unwrap(), tv);
+        let tv = Timeval::new(951827696, 0);
+        assert_eq!(
+            serde_json::from_str::<Timeval>(r#""2000-02-29T13:34:56+01:00""#).unwrap(),
+            tv
+        );
+        assert_eq!(
+            serde_json::to_string(&tv).unwrap(),
+            r#""2000-02-29T12:34:56.000000Z""#
+        );
+        assert!(serde_json::from_str::<Timeval>(r#""2000-02-29 12:34""#).is_err());
+        for invalid in [
+            "2001-02-29T12:34:56Z",
+            "2000-04-31T12:34:56Z",
+            "2000-02-29T24:00:00Z",
+            "2000-02-29T12:34:61Z",
+            "2000-02-29T12:34:56+24:00",
+            "2000-02-29T12:34:56+-1:00",
+        ] {
+            let json = format!("\"{}\"", invalid);
+            assert!(
+                serde_json::from_str::<Timeval>(&json).is_err(),
+                "{}",
+                invalid
+            );
+        }
+        let unnormalized = Timeval::new(951827695, 1_000_000);
+        let json = serde_json::to_string(&unnormalized).unwrap();
+        assert_eq!(json, r#""2000-02-29T12:34:56.000000Z""#);
+        assert_eq!(serde_json::from_str::<Timeval>(&json).unwrap(), tv);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Timeval::new(10, 999_999);
+        let b = a.checked_add(Duration::from_micros(2)).unwrap();
+        assert_eq!(b, Timeval::new(11, 1));
+        assert_eq!(b.checked_sub(Duration::from_micros(2)), Some(a));
+        assert_eq!(a.checked_sub(Duration::from_secs(11)), None);
+        assert_eq!(
+            Timeval::new(u32::MAX, 0).checked_add(Duration::from_secs(1)),
+            None
+        );
+        let unnormalized = Timeval::new(0, u32::MAX);
+        assert_eq!(
+            Duration::from(unnormalized),
+            Duration::from_micros(u32::MAX as u64)
+        );
+        assert!(Timeval::try_from(Duration::from_secs(1 << 32)).is_err());
+        assert_eq!(b.checked_duration_since(a), Some(Duration::from_micros(2)));
+        assert_eq!(a.checked_duration_since(b), None);
+        assert!(a < b);
     }
 }
@@ -1,8 +1,14 @@
-use crate::{Address, Flags, ICMPExtension, Timeval, WartsSized};
+use crate::{Address, Flags, ICMPExtension, IcmpKind, StopCause, Timeval, WartsSized};
+use bitflags::bitflags;
+use deku::bitvec::{BitSlice, BitVec, Msb0};
+use deku::ctx::Endian;
 use deku::prelude::*;
+use std::ffi::CString;
+use std::time::Duration;
 
 /// Traceroute type.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
 pub enum TraceType {
     /// ICMP echo requests.
@@ -19,7 +25,8 @@ pub enum TraceType {
     TCPAck = 0x06,
 }
 
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
 pub enum TraceGapAction {
     /// Null action.
@@ -30,8 +37,112 @@ pub enum TraceGapAction {
     LastDitch = 0x02,
 }
 
+/// What to do when a loop is found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum TraceLoopAction {
+    /// Stop when a loop is found.
+    #[deku(id = "0")]
+    Quit,
+    /// Keep probing.
+    #[deku(id = "1")]
+    Continue,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+bitflags! {
+    /// Traceroute options.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TraceFlags: u32 {
+        /// Send all allotted probes at each hop.
+        const ALLATTEMPTS = 0x01;
+        /// Conduct path MTU discovery.
+        const PMTUD = 0x02;
+        /// Use datalink-layer timestamps.
+        const DL = 0x04;
+        /// Ignore replies from the destination at TTLs lower than expected.
+        const IGNORETTLDST = 0x08;
+        /// Doubletree was used.
+        const DOUBLETREE = 0x10;
+        /// Vary the ICMP checksum rather than the destination port (ICMP Paris).
+        const ICMPCSUMDP = 0x20;
+        /// Do not vary the payload of the probes.
+        const CONSTPAYLOAD = 0x40;
+        /// Use the socket error queue to receive replies.
+        const RXERR = 0x80;
+        /// Look up the PTR records of the hop addresses.
+        const PTR = 0x100;
+        /// Send probes using raw sockets.
+        const RAW = 0x200;
+    }
+}
+
+bitflags! {
+    /// Properties of a hop reply.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TraceHopFlags: u8 {
+        /// The reply was timestamped by the kernel on receipt.
+        const TS_SOCK_RX = 0x01;
+        /// The probe was timestamped by the datalink on transmission.
+        const TS_DL_TX = 0x02;
+        /// The reply was timestamped by the datalink on receipt.
+        const TS_DL_RX = 0x04;
+        /// The timestamps were taken from the TSC.
+        const TS_TSC = 0x08;
+        /// The reply TTL is included.
+        const REPLY_TTL = 0x10;
+        /// The reply is a TCP packet.
+        const TCP = 0x20;
+        /// The reply is a UDP packet.
+        const UDP = 0x40;
+    }
+}
+
+bitflags! {
+    /// Properties of a DoubleTree traceroute.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TraceDtreeFlags: u8 {
+        /// Backward probing, towards the vantage point, was disabled.
+        const NO_BACKWARD = 0x01;
+    }
+}
+
+impl_deku_bitflags!(TraceHopFlags, u8);
+impl_deku_bitflags!(TraceDtreeFlags, u8);
+
+/// Trace flags are stored with the same variable-length encoding as [`Flags`].
+impl DekuRead<'_, Endian> for TraceFlags {
+    fn read(
+        input: &'_ BitSlice<u8, Msb0>,
+        endian: Endian,
+    ) -> Result<(&'_ BitSlice<u8, Msb0>, Self), DekuError> {
+        let (rest, flags) = Flags::read(input, endian)?;
+        let bits = u32::try_from(flags.bits())
+            .map_err(|_| DekuError::Parse(format!("invalid trace flags {:?}", flags)))?;
+        Ok((rest, TraceFlags::from_bits_retain(bits)))
+    }
+}
+
+impl DekuWrite<Endian> for TraceFlags {
+    fn write(&self, output: &mut BitVec<u8, Msb0>, endian: Endian) -> Result<(), DekuError> {
+        Flags::new(self.bits() as u64).write(output, endian)
+    }
+}
+
+impl WartsSized for TraceFlags {
+    fn warts_size(&self) -> usize {
+        Flags::new(self.bits() as u64).warts_size()
+    }
+}
+
 /// Reason for the termination of a trace command.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
 pub enum TraceStopReason {
     /// Null reason.
@@ -57,14 +168,18 @@ pub enum TraceStopReason {
 }
 
 /// A traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct Traceroute {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
     /// Flags describing traceroute parameters and high-level outcomes.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     /// Parameter length, included if any flags are set.
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// List ID assigned by warts, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
@@ -89,7 +204,7 @@ pub struct Traceroute {
     pub stop_data: Option<u8>,
     /// Trace flags, included if flag 8 is set.
     #[deku(cond = "flags.get(8)")]
-    pub trace_flags: Option<Flags>,
+    pub trace_flags: Option<TraceFlags>,
     /// Attempts, included if flag 9 is set.
     #[deku(cond = "flags.get(9)")]
     pub attempts: Option<u8>,
@@ -131,7 +246,7 @@ pub struct Traceroute {
     pub gap_limit_action: Option<TraceGapAction>,
     /// What to do when a loop is found, included if flag 22 is set.
     #[deku(cond = "flags.get(22)")]
-    pub loop_action: Option<u8>,
+    pub loop_action: Option<TraceLoopAction>,
     /// Number of probes sent, included if flag 23 is set.
     #[deku(cond = "flags.get(23)")]
     pub probes_sent: Option<u16>,
@@ -158,22 +273,320 @@ pub struct Traceroute {
     #[deku(cond = "flags.get(30)")]
     pub router_addr: Option<Address>,
     /// Hop record count.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub hop_count: u16,
     /// Hop records, if hop record count > 0.
     #[deku(count = "hop_count")]
     pub hops: Vec<TraceProbe>,
-    // TODO: Optional PMTUD, DoubleTree data.
-    /// End of traceroute record; value is zero.
-    #[deku(assert_eq = "0")]
+    /// Path MTU discovery data, if the traceroute was run with PMTUD.
+    #[deku(
+        reader = "read_attribute(deku::rest, endian, TRACE_ATTR_PMTUD)",
+        writer = "write_attribute(deku::output, pmtud, endian, TRACE_ATTR_PMTUD)"
+    )]
+    pub pmtud: Option<TracePmtud>,
+    /// Probes sent with a TTL of 255 once the gap limit was reached, if the gap limit
+    /// action is [`TraceGapAction::LastDitch`].
+    #[deku(
+        reader = "read_attribute(deku::rest, endian, TRACE_ATTR_LAST_DITCH)",
+        writer = "write_attribute(deku::output, last_ditch, endian, TRACE_ATTR_LAST_DITCH)"
+    )]
+    pub last_ditch: Option<TraceLastDitch>,
+    /// DoubleTree data, if the traceroute was run with DoubleTree.
+    #[deku(
+        reader = "read_attribute(deku::rest, endian, TRACE_ATTR_DTREE)",
+        writer = "write_attribute(deku::output, dtree, endian, TRACE_ATTR_DTREE)"
+    )]
+    pub dtree: Option<TraceDtree>,
+    /// Attributes not decoded into the fields above, such as attributes of a type not
+    /// known to this crate, written back after them.
+    #[deku(
+        reader = "read_other_attributes(deku::rest, endian)",
+        writer = "write_other_attributes(deku::output, other_attributes, endian)"
+    )]
+    pub other_attributes: Vec<TraceAttribute>,
+    /// End of the traceroute attributes; value is zero. Reading it consumes the
+    /// attributes read by the fields above.
+    #[deku(reader = "read_attributes_end(deku::rest, endian)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub eof: u16,
 }
 
+/// A raw traceroute attribute, identified by its 5-bit type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceAttribute {
+    pub attr_type: u8,
+    pub data: Vec<u8>,
+}
+
+/// Path MTU discovery data of a traceroute.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TracePmtud {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// MTU of the interface used to send probes, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub if_mtu: Option<u16>,
+    /// Path MTU discovered, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub path_mtu: Option<u16>,
+    /// MTU of the first hop, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub out_mtu: Option<u16>,
+    /// Version of the PMTUD data, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub version: Option<u8>,
+    /// Number of notes, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub note_count: Option<u8>,
+    /// Notes describing the MTU changes along the path.
+    #[deku(count = "note_count.unwrap_or(0)")]
+    pub notes: Vec<TracePmtudNote>,
+    /// PMTUD probe record count.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub hop_count: u16,
+    /// PMTUD probe records.
+    #[deku(count = "hop_count")]
+    pub hops: Vec<TraceProbe>,
+}
+
+/// Kind of PMTUD note.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum TracePmtudNoteType {
+    /// A packet too big message was received.
+    PTB = 0x01,
+    /// A packet too big message with an invalid next-hop MTU was received.
+    PTBBad = 0x02,
+    /// No reply was received, the MTU was inferred.
+    Silence = 0x03,
+}
+
+/// A note describing an MTU change found during path MTU discovery.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TracePmtudNote {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Kind of note, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub note_type: Option<TracePmtudNoteType>,
+    /// Next-hop MTU, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub next_hop_mtu: Option<u16>,
+    /// Index of the corresponding PMTUD probe record plus one, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub hop_index: Option<u16>,
+}
+
+/// Last-ditch probes of a traceroute, kept apart from the regular hop records.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TraceLastDitch {
+    /// Parameter flags, kept with `serde-skip-wire` since they number the unknown parameters.
+    pub flags: Flags,
+    /// Length of the parameters; scamper defines no last-ditch parameters, so the block
+    /// is normally empty.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Parameters not known to this crate, skipped using the parameter length.
+    #[deku(count = "param_length.unwrap_or(0)")]
+    pub unknown_params: Vec<u8>,
+    /// Last-ditch probe record count.
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub hop_count: u16,
+    /// Last-ditch probe records.
+    #[deku(count = "hop_count")]
+    pub hops: Vec<TraceProbe>,
+}
+
+/// DoubleTree data of a traceroute.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct TraceDtree {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Address in the local stop set that stopped backward probing, ID corresponding to
+    /// global warts address (deprecated); included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub lss_stop_addr_id: Option<u32>,
+    /// Address in the global stop set that stopped forward probing, ID corresponding to
+    /// global warts address (deprecated); included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub gss_stop_addr_id: Option<u32>,
+    /// TTL at which probing started, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub first_hop: Option<u8>,
+    /// Address in the local stop set that stopped backward probing, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub lss_stop: Option<Address>,
+    /// Address in the global stop set that stopped forward probing, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub gss_stop: Option<Address>,
+    /// Name of the local stop set, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
+    pub lss_name: Option<CString>,
+    /// DoubleTree flags, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub dtree_flags: Option<TraceDtreeFlags>,
+}
+
+/// End of the optional traceroute attributes.
+const TRACE_ATTR_EOF: u16 = 0x0;
+/// Path MTU discovery attribute.
+const TRACE_ATTR_PMTUD: u16 = 0x1;
+/// Last-ditch probes attribute.
+const TRACE_ATTR_LAST_DITCH: u16 = 0x2;
+/// DoubleTree attribute.
+const TRACE_ATTR_DTREE: u16 = 0x3;
+
+/// Splits the attributes following the hop records of a traceroute, up to and including
+/// the end-of-attributes header. Each attribute starts with a 16-bit header holding a 5-bit
+/// type and an 11-bit length in bytes, `(type << 11) | length`; an all-zero header ends the
+/// list. Returns the type and body of each attribute, and the data after the list.
+#[allow(clippy::type_complexity)]
+fn split_attributes(
+    mut rest: &BitSlice<u8, Msb0>,
+    endian: Endian,
+) -> Result<(Vec<(u16, &BitSlice<u8, Msb0>)>, &BitSlice<u8, Msb0>), DekuError> {
+    let mut attributes = Vec::new();
+    loop {
+        let (after_header, header) = u16::read(rest, endian)?;
+        if header == TRACE_ATTR_EOF {
+            return Ok((attributes, after_header));
+        }
+        let length = ((header & 0x7ff) as usize) * 8;
+        if after_header.len() < length {
+            return Err(DekuError::Incomplete(deku::error::NeedSize::new(length)));
+        }
+        let (body, after) = after_header.split_at(length);
+        attributes.push(((header >> 11) & 0x1f, body));
+        rest = after;
+    }
+}
+
+/// Reads an optional attribute following the hop records of a traceroute. Attributes may
+/// appear in any order, so the whole list is searched without consuming it; attributes of
+/// other (or unknown) types are skipped using their length.
+fn read_attribute<'a, T: DekuRead<'a, Endian>>(
+    rest: &'a BitSlice<u8, Msb0>,
+    endian: Endian,
+    attr_type: u16,
+) -> Result<(&'a BitSlice<u8, Msb0>, Option<T>), DekuError> {
+    let (attributes, _) = split_attributes(rest, endian)?;
+    let value = match attributes.into_iter().find(|(t, _)| *t == attr_type) {
+        Some((_, body)) => Some(T::read(body, endian)?.1),
+        None => None,
+    };
+    Ok((rest, value))
+}
+
+/// Reads the attributes that [`read_attribute`] does not decode: attributes of unknown
+/// types, and repeated attributes of a known type.
+fn read_other_attributes(
+    rest: &BitSlice<u8, Msb0>,
+    endian: Endian,
+) -> Result<(&BitSlice<u8, Msb0>, Vec<TraceAttribute>), DekuError> {
+    let (attributes, _) = split_attributes(rest, endian)?;
+    let mut seen = Vec::new();
+    let mut others = Vec::new();
+    for (attr_type, body) in attributes {
+        let known =
+            [TRACE_ATTR_PMTUD, TRACE_ATTR_LAST_DITCH, TRACE_ATTR_DTREE].contains(&attr_type);
+        if known && !seen.contains(&attr_type) {
+            seen.push(attr_type);
+        } else {
+            others.push(TraceAttribute {
+                attr_type: attr_type as u8,
+                data: body.to_bitvec().into_vec(),
+            });
+        }
+    }
+    Ok((rest, others))
+}
+
+/// Consumes the attributes following the hop records of a traceroute, up to and including
+/// the end-of-attributes header.
+fn read_attributes_end(
+    rest: &BitSlice<u8, Msb0>,
+    endian: Endian,
+) -> Result<(&BitSlice<u8, Msb0>, u16), DekuError> {
+    let (_, after) = split_attributes(rest, endian)?;
+    Ok((after, TRACE_ATTR_EOF))
+}
+
+/// Writes an optional attribute following the hop records of a traceroute.
+fn write_attribute<T: DekuWrite<Endian> + WartsSized>(
+    output: &mut BitVec<u8, Msb0>,
+    value: &Option<T>,
+    endian: Endian,
+    attr_type: u16,
+) -> Result<(), DekuError> {
+    if let Some(value) = value {
+        let size = value.warts_size();
+        if size > 0x7ff {
+            return Err(DekuError::InvalidParam(format!(
+                "traceroute attribute of {size} bytes exceeds the 11-bit length"
+            )));
+        }
+        let header = (attr_type << 11) | size as u16;
+        header.write(output, endian)?;
+        value.write(output, endian)?;
+    }
+    Ok(())
+}
+
+/// Writes the attributes that are not decoded into typed fields.
+fn write_other_attributes(
+    output: &mut BitVec<u8, Msb0>,
+    attributes: &[TraceAttribute],
+    endian: Endian,
+) -> Result<(), DekuError> {
+    for attribute in attributes {
+        if attribute.attr_type > 0x1f || attribute.data.len() > 0x7ff {
+            return Err(DekuError::InvalidParam(format!(
+                "traceroute attribute of type {} and {} bytes exceeds its header",
+                attribute.attr_type,
+                attribute.data.len()
+            )));
+        }
+        let header = ((attribute.attr_type as u16) << 11) | attribute.data.len() as u16;
+        header.write(output, endian)?;
+        attribute.data.write(output, endian)?;
+    }
+    Ok(())
+}
+
 /// A traceroute probe and its associated reply, if any.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct TraceProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// Hop address, ID corresponding to global warts address; included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
@@ -186,7 +599,7 @@ pub struct TraceProbe {
     pub reply_ttl: Option<u8>,
     /// Hop flags, included if flag 4 is set
     #[deku(cond = "flags.get(4)")]
-    pub hop_flags: Option<u8>,
+    pub hop_flags: Option<TraceHopFlags>,
     /// Hop probe ID - how many probes have been sent for the given TTL. Included if flag 5 is set.
     #[deku(cond = "flags.get(5)")]
     pub probe_id: Option<u8>,
@@ -237,11 +650,10 @@ pub struct TraceProbe {
     /// ICMP extension total length, included if flag 17 is set.
     #[deku(cond = "flags.get(17)")]
     pub icmp_extensions_length: Option<u16>,
-    // NOTE: We currently assume that there is at maximum one ICMP extension, and that it
-    // contains an MPLS label stack.
+    /// ICMP extension objects, included if flag 17 is set.
     #[deku(
         cond = "flags.get(17)",
-        count = "if icmp_extensions_length.unwrap() > 0 { 1 } else { 0 }"
+        bytes_read = "icmp_extensions_length.unwrap_or(0)"
     )]
     pub icmp_extensions: Vec<ICMPExtension>,
     /// Hop address, included if flag 18 is set.
@@ -253,6 +665,34 @@ pub struct TraceProbe {
 }
 
 impl Traceroute {
+    /// Returns the number of consecutive unresponsive hops after which DoubleTree stops
+    /// probing backward, which is the gap limit of the traceroute, if it was run with
+    /// DoubleTree.
+    pub fn dtree_gap_limit(&self) -> Option<u8> {
+        self.dtree.as_ref().and(self.gap_limit)
+    }
+
+    /// Returns why the traceroute stopped, if a stop reason was recorded and the
+    /// destination address is resolved (see [`crate::Object::dereference`]), as its family
+    /// determines the meaning of ICMP codes. For an ICMP stop, the code is taken from the
+    /// stop hop, the last hop record, if its reply is of the recorded ICMP type.
+    pub fn stop_cause(&self) -> Option<StopCause> {
+        let ipv6 = self.dst_addr?.ip()?.is_ipv6();
+        let mut cause = StopCause::from_trace(self.stop_reason?, self.stop_data.unwrap_or(0), ipv6);
+        if let StopCause::Icmp {
+            icmp_type,
+            icmp_code,
+        } = &mut cause
+        {
+            *icmp_code = self
+                .hops
+                .last()
+                .filter(|hop| hop.icmp_type == Some(*icmp_type))
+                .and_then(|hop| hop.icmp_code);
+        }
+        Some(cause)
+    }
+
     pub fn finalize(mut self) -> Self {
         let mut flags = Vec::new();
         let mut param_length = 0;
@@ -295,11 +735,103 @@ impl Traceroute {
             + param_length
             + self.hop_count.warts_size()
             + hops_size
+            + attribute_size(&self.pmtud)
+            + attribute_size(&self.last_ditch)
+            + attribute_size(&self.dtree)
+            + self
+                .other_attributes
+                .iter()
+                .map(|attribute| 2 + attribute.data.len())
+                .sum::<usize>()
             + self.eof.warts_size()) as u32;
         self
     }
 }
 
+impl Traceroute {
+    /// Returns true if a last-ditch probe got a reply from the destination, i.e. if the
+    /// destination was reachable although the traceroute stopped at the gap limit.
+    pub fn reached_by_last_ditch(&self) -> bool {
+        match (&self.last_ditch, self.dst_addr) {
+            (Some(last_ditch), Some(dst_addr)) => {
+                last_ditch.hops.iter().any(|hop| hop.addr == Some(dst_addr))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Size of an optional attribute, including its header.
+fn attribute_size<T: WartsSized>(value: &Option<T>) -> usize {
+    value.as_ref().map_or(0, |value| 2 + value.warts_size())
+}
+
+impl TracePmtud {
+    /// Returns the PMTUD probe record referenced by a note, if any.
+    pub fn note_hop(&self, note: &TracePmtudNote) -> Option<&TraceProbe> {
+        let index = note.hop_index?.checked_sub(1)?;
+        self.hops.get(index as usize)
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.note_count = Some(self.notes.len() as u8);
+        self.hop_count = self.hops.len() as u16;
+        push_flag!(flags, param_length, 1, self.if_mtu);
+        push_flag!(flags, param_length, 2, self.path_mtu);
+        push_flag!(flags, param_length, 3, self.out_mtu);
+        push_flag!(flags, param_length, 4, self.version);
+        push_flag!(flags, param_length, 5, self.note_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TraceLastDitch {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        if !self.unknown_params.is_empty() {
+            flags.extend((1..64).filter(|&i| self.flags.get(i as usize)));
+        }
+        self.flags = Flags::from(flags);
+        self.param_length = self.flags.any().then_some(self.unknown_params.len() as u16);
+        self.hop_count = self.hops.len() as u16;
+        self
+    }
+}
+
+impl TraceDtree {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.lss_stop_addr_id);
+        push_flag!(flags, param_length, 2, self.gss_stop_addr_id);
+        push_flag!(flags, param_length, 3, self.first_hop);
+        push_flag!(flags, param_length, 4, self.lss_stop);
+        push_flag!(flags, param_length, 5, self.gss_stop);
+        push_flag!(flags, param_length, 6, self.lss_name);
+        push_flag!(flags, param_length, 7, self.dtree_flags);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl TracePmtudNote {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        push_flag!(flags, param_length, 1, self.note_type);
+        push_flag!(flags, param_length, 2, self.next_hop_mtu);
+        push_flag!(flags, param_length, 3, self.hop_index);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
 impl TraceProbe {
     pub fn finalize(mut self) -> Self {
         let mut flags = Vec::new();
@@ -321,17 +853,44 @@ impl TraceProbe {
         push_flag!(flags, param_length, 14, self.quoted_ttl);
         push_flag!(flags, param_length, 15, self.reply_tcp_flags);
         push_flag!(flags, param_length, 16, self.quoted_tos);
+        self.icmp_extensions_length =
+            (!self.icmp_extensions.is_empty()).then(|| self.icmp_extensions.warts_size() as u16);
         push_flag!(flags, param_length, 17, self.icmp_extensions_length);
-        // TODO: push_flag!(flags, length, 17, self.icmp_extensions);
+        param_length += self.icmp_extensions.warts_size();
         push_flag!(flags, param_length, 18, self.addr);
         push_flag!(flags, param_length, 19, self.tx.as_ref());
         self.flags = Flags::from(flags);
         self.param_length = Some(param_length as u16);
         self
     }
+
+    /// Classifies the ICMP reply to this hop probe, as ICMPv4 or ICMPv6 according to the
+    /// hop address; `None` if there is no ICMP reply or the hop address is still a
+    /// reference (see [`crate::Object::dereference`]).
+    pub fn icmp_kind(&self) -> Option<IcmpKind> {
+        IcmpKind::from_reply(self.icmp_type, self.icmp_code, self.addr.as_ref())
+    }
+
     pub fn rtt_ms(&self) -> Option<f64> {
         self.rtt_usec.map(|x| x as f64 / 1000.0)
     }
+
+    /// Time elapsed between sending the probe and receiving the hop reply.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt_usec.map(|x| Duration::from_micros(x as u64))
+    }
+
+    /// Time at which the hop reply arrived, computed as `tx + rtt` since warts does not
+    /// store receive times for traceroute hops.
+    pub fn rx(&self) -> Option<Timeval> {
+        self.tx?.checked_add(self.rtt()?)
+    }
+}
+
+impl WartsSized for TraceLoopAction {
+    fn warts_size(&self) -> usize {
+        1
+    }
 }
 
 impl WartsSized for TraceType {
@@ -352,6 +911,49 @@ impl WartsSized for TraceStopReason {
     }
 }
 
+impl WartsSized for TracePmtud {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+            + self.notes.warts_size()
+            + self.hop_count.warts_size()
+            + self.hops.warts_size()
+    }
+}
+
+impl WartsSized for TraceLastDitch {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.unknown_params.len()
+            + self.hop_count.warts_size()
+            + self.hops.warts_size()
+    }
+}
+
+impl WartsSized for TraceDtree {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+impl WartsSized for TracePmtudNoteType {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for TracePmtudNote {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
 impl WartsSized for TraceProbe {
     fn warts_size(&self) -> usize {
         self.flags.warts_size()
@@ -359,3 +961,257 @@ impl WartsSized for TraceProbe {
             + self.param_length.unwrap() as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, ICMPExtension, ICMPExtensionBody, InterfaceInformation, InterfaceRole, Object,
+        TraceAttribute, TraceDtree, TraceDtreeFlags, TraceFlags, TraceGapAction, TraceHopFlags,
+        TraceLastDitch, TraceLoopAction, TracePmtud, TracePmtudNote, TracePmtudNoteType,
+        Traceroute,
+    };
+    use deku::DekuContainerWrite;
+    use std::ffi::CString;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn traceroute() -> Traceroute {
+        let data = include_bytes!("../data/trace_google_dns_v4_default.warts");
+        Object::all_from_bytes(data)
+            .into_iter()
+            .find_map(|object| match object {
+                Object::Traceroute(t) => Some(t),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn round_trip(trace: Traceroute) -> Traceroute {
+        let trace = trace.finalize();
+        let bytes = Object::Traceroute(trace.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + trace.length as usize);
+        let parsed = Object::all_from_bytes(&bytes);
+        assert_eq!(parsed, vec![Object::Traceroute(trace.clone())]);
+        trace
+    }
+
+    #[test]
+    fn flags_round_trip() {
+        let mut trace = traceroute();
+        // RXERR is the eighth bit, and needs two bytes in the variable-length encoding.
+        trace.trace_flags = Some(
+            TraceFlags::ALLATTEMPTS | TraceFlags::RXERR | TraceFlags::from_bits_retain(1 << 20),
+        );
+        trace.loop_action = Some(TraceLoopAction::Unknown(7));
+        trace.hops[0].hop_flags =
+            Some(TraceHopFlags::REPLY_TTL | TraceHopFlags::from_bits_retain(0x80));
+        trace.hops[0] = trace.hops[0].clone().finalize();
+        let trace = round_trip(trace);
+        let hop_flags = trace.hops[0].hop_flags.unwrap();
+        assert!(hop_flags.contains(TraceHopFlags::REPLY_TTL));
+        assert!(!hop_flags.contains(TraceHopFlags::TS_SOCK_RX));
+        assert_eq!(hop_flags.bits(), 0x90);
+    }
+
+    #[test]
+    fn icmp_extensions_round_trip() {
+        let mut trace = traceroute();
+        let information = InterfaceInformation {
+            role: InterfaceRole::Incoming,
+            if_index: Some(12),
+            address: Some(IpAddr::from(Ipv4Addr::new(192, 0, 2, 1))),
+            name: Some("ge-0/0/1".to_string()),
+            mtu: Some(9000),
+        };
+        let extensions = vec![
+            ICMPExtension::new(vec![]),
+            ICMPExtension::from_interface_information(information.clone()),
+            ICMPExtension::from_raw(3, 1, vec![0xde, 0xad, 0xbe, 0xef]),
+        ];
+        trace.hops[0].icmp_extensions = extensions.clone();
+        trace.hops[0] = trace.hops[0].clone().finalize();
+        let trace = round_trip(trace);
+        let hop = &trace.hops[0];
+        // 4-byte headers, 4 (ifIndex) + 8 (address) + 12 (name) + 4 (MTU) and 4 raw bytes.
+        assert_eq!(hop.icmp_extensions_length, Some(44));
+        assert_eq!(hop.icmp_extensions, extensions);
+        assert_eq!(hop.icmp_extensions[1].ext_type(), 0x0f);
+        assert_eq!(
+            hop.icmp_extensions[1].interface_information(),
+            Some(&information)
+        );
+        assert!(matches!(
+            hop.icmp_extensions[2].body(),
+            ICMPExtensionBody::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn pmtud_round_trip() {
+        let mut trace = traceroute();
+        let hop = trace.hops[0].clone();
+        let note = TracePmtudNote {
+            flags: Default::default(),
+            param_length: None,
+            note_type: Some(TracePmtudNoteType::PTB),
+            next_hop_mtu: Some(1280),
+            hop_index: Some(1),
+        }
+        .finalize();
+        trace.pmtud = Some(
+            TracePmtud {
+                flags: Default::default(),
+                param_length: None,
+                if_mtu: Some(1500),
+                path_mtu: Some(1280),
+                out_mtu: Some(1500),
+                version: Some(2),
+                note_count: None,
+                notes: vec![note],
+                hop_count: 0,
+                hops: vec![hop.clone()],
+            }
+            .finalize(),
+        );
+        let trace = round_trip(trace);
+        let pmtud = trace.pmtud.as_ref().unwrap();
+        assert_eq!(pmtud.note_hop(&pmtud.notes[0]), Some(&hop));
+    }
+
+    #[test]
+    fn attributes_in_any_order() {
+        let trace = traceroute().finalize();
+        let mut bytes = Object::Traceroute(trace.clone()).to_bytes().unwrap();
+        // Replace the end-of-attributes header by a DoubleTree attribute (type 3, 4 bytes:
+        // flag 3 set, parameter length 1, first hop 2), preceded by an attribute of unknown
+        // type 7 which must be kept, and followed by the end-of-attributes header.
+        bytes.truncate(bytes.len() - 2);
+        #[rustfmt::skip]
+        bytes.extend_from_slice(&[
+            0x38, 0x02, 0xaa, 0xbb,
+            0x18, 0x04, 0x04, 0x00, 0x01, 0x02,
+            0x00, 0x00,
+        ]);
+        let length = bytes.len() as u32 - 8;
+        bytes[4..8].copy_from_slice(&length.to_be_bytes());
+        let parsed = Object::all_from_bytes(&bytes);
+        let Object::Traceroute(parsed) = &parsed[0] else {
+            panic!("expected a traceroute");
+        };
+        assert_eq!(parsed.pmtud, None);
+        assert_eq!(parsed.last_ditch, None);
+        let dtree = parsed.dtree.clone().unwrap();
+        assert_eq!(dtree.first_hop, Some(2));
+        assert_eq!(
+            parsed.other_attributes,
+            vec![TraceAttribute {
+                attr_type: 7,
+                data: vec![0xaa, 0xbb]
+            }]
+        );
+        // The unknown attribute is written back after the DoubleTree one, whose header
+        // keeps its 5-bit type, with or without finalizing the traceroute.
+        let written = Object::Traceroute(parsed.clone()).to_bytes().unwrap();
+        assert_eq!(written.len(), bytes.len());
+        #[rustfmt::skip]
+        assert_eq!(
+            written[written.len() - 12..],
+            [0x18, 0x04, 0x04, 0x00, 0x01, 0x02, 0x38, 0x02, 0xaa, 0xbb, 0x00, 0x00]
+        );
+        let finalized = Object::Traceroute(parsed.clone().finalize());
+        assert_eq!(finalized.to_bytes().unwrap(), written);
+        assert_eq!(
+            Object::all_from_bytes(&written),
+            vec![Object::Traceroute(parsed.clone())]
+        );
+    }
+
+    #[test]
+    fn last_ditch_parameters() {
+        let trace = traceroute().finalize();
+        let mut bytes = Object::Traceroute(trace).to_bytes().unwrap();
+        // Last-ditch attribute (type 2, 7 bytes): flag 1 (unknown) set with 2 bytes of
+        // parameters, followed by a hop count of zero.
+        bytes.truncate(bytes.len() - 2);
+        #[rustfmt::skip]
+        bytes.extend_from_slice(&[
+            0x10, 0x07, 0x01, 0x00, 0x02, 0xaa, 0xbb, 0x00, 0x00,
+            0x00, 0x00,
+        ]);
+        let length = bytes.len() as u32 - 8;
+        bytes[4..8].copy_from_slice(&length.to_be_bytes());
+        let parsed = Object::all_from_bytes(&bytes);
+        let Object::Traceroute(trace) = &parsed[0] else {
+            panic!("expected a traceroute");
+        };
+        let last_ditch = trace.last_ditch.clone().unwrap();
+        assert_eq!(last_ditch.unknown_params, vec![0xaa, 0xbb]);
+        assert!(last_ditch.hops.is_empty());
+        assert_eq!(parsed[0].to_bytes().unwrap(), bytes);
+        let trace = Object::Traceroute(trace.clone().finalize());
+        assert_eq!(trace.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn dtree_round_trip() {
+        let mut trace = traceroute();
+        trace.dtree = Some(
+            TraceDtree {
+                flags: Default::default(),
+                param_length: None,
+                lss_stop_addr_id: None,
+                gss_stop_addr_id: None,
+                first_hop: Some(4),
+                lss_stop: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+                gss_stop: None,
+                lss_name: Some(CString::new("ark").unwrap()),
+                dtree_flags: Some(TraceDtreeFlags::NO_BACKWARD),
+            }
+            .finalize(),
+        );
+        trace.gap_limit = Some(3);
+        let trace = round_trip(trace);
+        assert_eq!(trace.dtree_gap_limit(), Some(3));
+        let dtree_flags = trace.dtree.unwrap().dtree_flags.unwrap();
+        assert!(dtree_flags.contains(TraceDtreeFlags::NO_BACKWARD));
+    }
+
+    #[test]
+    fn last_ditch_round_trip() {
+        let mut trace = traceroute();
+        let mut hop = trace.hops.last().unwrap().clone();
+        hop.probe_ttl = Some(255);
+        hop.addr = trace.dst_addr;
+        trace.gap_limit_action = Some(TraceGapAction::LastDitch);
+        trace.last_ditch = Some(
+            TraceLastDitch {
+                flags: Default::default(),
+                param_length: None,
+                unknown_params: vec![],
+                hop_count: 0,
+                hops: vec![hop.finalize()],
+            }
+            .finalize(),
+        );
+        // The regular hops and the last-ditch probes are distinct, and all three attributes
+        // can be present at the same time.
+        trace.dtree = Some(
+            TraceDtree {
+                flags: Default::default(),
+                param_length: None,
+                lss_stop_addr_id: None,
+                gss_stop_addr_id: None,
+                first_hop: Some(1),
+                lss_stop: None,
+                gss_stop: None,
+                lss_name: None,
+                dtree_flags: None,
+            }
+            .finalize(),
+        );
+        let hop_count = trace.hops.len();
+        let trace = round_trip(trace);
+        assert_eq!(trace.hops.len(), hop_count);
+        assert_eq!(trace.last_ditch.as_ref().unwrap().hops.len(), 1);
+        assert!(trace.reached_by_last_ditch());
+    }
+}
@@ -1,14 +1,81 @@
-use crate::{Address, Flags, ICMPExtension, Timeval, WartsSized};
+use crate::object::AddressSlot;
+use crate::{Address, Flags, ICMPExtension, IcmpKind, Timeval, WartsSized};
+use bitflags::bitflags;
 use deku::prelude::*;
 use std::ffi::CString;
+use std::time::Duration;
+
+/// MDA traceroute probing method.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum TracelbType {
+    /// UDP packets, varying the destination port.
+    #[deku(id = "0x01")]
+    UDPDport,
+    /// ICMP echo requests, varying the checksum.
+    #[deku(id = "0x02")]
+    ICMPEcho,
+    /// UDP packets, varying the source port.
+    #[deku(id = "0x03")]
+    UDPSport,
+    /// TCP SYN packets, varying the source port.
+    #[deku(id = "0x04")]
+    TCPSport,
+    /// TCP ACK packets, varying the source port.
+    #[deku(id = "0x05")]
+    TCPAckSport,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+bitflags! {
+    /// MDA traceroute options.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TracelbFlags: u8 {
+        /// Look up the PTR records of the node addresses.
+        const PTR = 0x01;
+    }
+}
+
+bitflags! {
+    /// Properties of an MDA traceroute node.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TracelbNodeFlags: u8 {
+        /// The quoted TTL is included.
+        const QTTL = 0x01;
+    }
+}
+
+bitflags! {
+    /// Properties of an MDA traceroute reply.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct TracelbReplyFlags: u8 {
+        /// The reply TTL is included.
+        const REPLY_TTL = 0x01;
+        /// The reply is a TCP packet.
+        const TCP = 0x02;
+    }
+}
+
+impl_deku_bitflags!(TracelbFlags, u8);
+impl_deku_bitflags!(TracelbNodeFlags, u8);
+impl_deku_bitflags!(TracelbReplyFlags, u8);
 
 /// An MDA traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MultipathTraceroute {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     #[deku(cond = "flags.get(1)")]
     pub list_id: Option<u32>,
@@ -26,8 +93,9 @@ pub struct MultipathTraceroute {
     pub dst_port: Option<u16>,
     #[deku(cond = "flags.get(8)")]
     pub probe_size: Option<u16>,
+    /// Probing method, included if flag 9 is set.
     #[deku(cond = "flags.get(9)")]
-    pub type_: Option<u8>,
+    pub type_: Option<TracelbType>,
     #[deku(cond = "flags.get(10)")]
     pub first_hop: Option<u8>,
     #[deku(cond = "flags.get(11)")]
@@ -41,8 +109,10 @@ pub struct MultipathTraceroute {
     #[deku(cond = "flags.get(15)")]
     pub ip_tos: Option<u8>,
     #[deku(cond = "flags.get(16)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub node_count: Option<u16>,
     #[deku(cond = "flags.get(17)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub link_count: Option<u16>,
     #[deku(cond = "flags.get(18)")]
     pub probe_count: Option<u32>,
@@ -56,9 +126,9 @@ pub struct MultipathTraceroute {
     pub dst_addr: Option<Address>,
     #[deku(cond = "flags.get(23)")]
     pub user_id: Option<u32>,
-    // TODO: Enum
+    /// Options, included if flag 24 is set.
     #[deku(cond = "flags.get(24)")]
-    pub flags2: Option<u8>,
+    pub flags2: Option<TracelbFlags>,
     #[deku(cond = "flags.get(25)")]
     pub router_addr: Option<Address>,
     #[deku(count = "node_count.unwrap_or(0)")]
@@ -68,18 +138,21 @@ pub struct MultipathTraceroute {
 }
 
 /// A node in a multipath traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MultipathTraceNode {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// Node address ID, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
     pub addr_id: Option<u32>,
     /// Node flags, included if flag 2 is set.
     #[deku(cond = "flags.get(2)")]
-    pub node_flags: Option<u8>,
+    pub node_flags: Option<TracelbNodeFlags>,
     /// Number of links, included if flag 3 is set.
     #[deku(cond = "flags.get(3)")]
     pub link_count: Option<u16>,
@@ -91,15 +164,22 @@ pub struct MultipathTraceNode {
     pub addr: Option<Address>,
     /// Included if flag 6 is set.
     #[deku(cond = "flags.get(6)")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::option_cstring")
+    )]
     pub name: Option<CString>,
 }
 
 /// A link in a multipath traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MultipathTraceLink {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// Link from, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
@@ -109,6 +189,7 @@ pub struct MultipathTraceLink {
     pub to: Option<u16>,
     /// Number of probe sets, included if flag 3 is set.
     #[deku(cond = "flags.get(3)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub probe_set_count: Option<u8>,
     /// Probe sets, if any.
     #[deku(count = "probe_set_count.unwrap_or(0)")]
@@ -116,11 +197,14 @@ pub struct MultipathTraceLink {
 }
 
 /// A set of probes in a multipath traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MultipathTraceProbeSet {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     /// Number of probes sent, included if flag 1 is set.
     #[deku(cond = "flags.get(1)")]
@@ -131,11 +215,14 @@ pub struct MultipathTraceProbeSet {
 }
 
 /// A probe in a multipath traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MultipathTraceProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     #[deku(cond = "flags.get(1)")]
     pub tx: Option<Timeval>,
@@ -152,11 +239,14 @@ pub struct MultipathTraceProbe {
 }
 
 /// A reply in a multipath traceroute.
-#[derive(Debug, PartialEq, DekuRead, DekuWrite)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
 pub struct MultipathTraceReply {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub flags: Flags,
     #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
     pub param_length: Option<u16>,
     #[deku(cond = "flags.get(1)")]
     pub rx: Option<Timeval>,
@@ -164,8 +254,9 @@ pub struct MultipathTraceReply {
     pub ip_id: Option<u16>,
     #[deku(cond = "flags.get(3)")]
     pub ttl: Option<u8>,
+    /// Reply flags, included if flag 4 is set.
     #[deku(cond = "flags.get(4)")]
-    pub reply_flags: Option<u8>,
+    pub reply_flags: Option<TracelbReplyFlags>,
     #[deku(cond = "flags.get(5)")]
     pub icmp_type: Option<u8>,
     #[deku(cond = "flags.get(5)")]
@@ -174,11 +265,10 @@ pub struct MultipathTraceReply {
     pub tcp_flags: Option<u8>,
     #[deku(cond = "flags.get(7)")]
     pub icmp_extensions_length: Option<u16>,
-    // NOTE: We currently assume that there is at maximum one ICMP extension, and that it
-    // contains an MPLS label stack.
+    /// ICMP extension objects, included if flag 7 is set.
     #[deku(
         cond = "flags.get(7)",
-        count = "if icmp_extensions_length.unwrap() > 0 { 1 } else { 0 }"
+        bytes_read = "icmp_extensions_length.unwrap_or(0)"
     )]
     pub icmp_extensions: Vec<ICMPExtension>,
     #[deku(cond = "flags.get(8)")]
@@ -192,6 +282,28 @@ pub struct MultipathTraceReply {
 }
 
 impl MultipathTraceroute {
+    /// Address fields of the parameters, nodes and replies.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        let mut slots = vec![
+            AddressSlot::Optional(&mut self.src_addr, self.src_addr_id),
+            AddressSlot::Optional(&mut self.dst_addr, self.dst_addr_id),
+            AddressSlot::Optional(&mut self.router_addr, None),
+        ];
+        for node in &mut self.nodes {
+            slots.push(AddressSlot::Optional(&mut node.addr, node.addr_id));
+        }
+        for reply in self
+            .links
+            .iter_mut()
+            .flat_map(|link| link.probe_sets.iter_mut())
+            .flat_map(|set| set.probes.iter_mut())
+            .flat_map(|probe| probe.replies.iter_mut())
+        {
+            slots.push(AddressSlot::Optional(&mut reply.addr, reply.addr_id));
+        }
+        slots
+    }
+
     pub fn finalize(mut self) -> Self {
         let mut flags = Vec::new();
         let mut param_length = 0;
@@ -291,7 +403,32 @@ impl MultipathTraceProbe {
     }
 }
 
+impl MultipathTraceProbe {
+    /// Round trip times of the replies to this probe, in the order of `replies`.
+    pub fn rtts(&self) -> impl Iterator<Item = Option<Duration>> + '_ {
+        self.replies.iter().map(|reply| reply.rtt(self))
+    }
+}
+
 impl MultipathTraceReply {
+    /// Classifies this reply from a load-balanced path, with the address family of the
+    /// replying interface; `None` for TCP replies, or if the reply address was not
+    /// dereferenced.
+    pub fn icmp_kind(&self) -> Option<IcmpKind> {
+        IcmpKind::from_reply(self.icmp_type, self.icmp_code, self.addr.as_ref())
+    }
+
+    /// Returns true if the reply is a TCP packet rather than an ICMP message.
+    pub fn is_tcp(&self) -> bool {
+        self.reply_flags
+            .is_some_and(|flags| flags.contains(TracelbReplyFlags::TCP))
+    }
+
+    /// Round trip time of the reply to `probe`, derived from the transmit and receive times.
+    pub fn rtt(&self, probe: &MultipathTraceProbe) -> Option<Duration> {
+        self.rx?.checked_duration_since(probe.tx?)
+    }
+
     pub fn finalize(mut self) -> Self {
         let mut flags = Vec::new();
         let mut param_length = 0;
@@ -302,8 +439,10 @@ impl MultipathTraceReply {
         push_flag!(flags, param_length, 5, self.icmp_type);
         push_flag!(flags, param_length, 5, self.icmp_code);
         push_flag!(flags, param_length, 6, self.tcp_flags);
+        self.icmp_extensions_length =
+            (!self.icmp_extensions.is_empty()).then(|| self.icmp_extensions.warts_size() as u16);
         push_flag!(flags, param_length, 7, self.icmp_extensions_length);
-        // TODO: icmp_extensions
+        param_length += self.icmp_extensions.warts_size();
         push_flag!(flags, param_length, 8, self.quoted_ttl);
         push_flag!(flags, param_length, 9, self.quoted_tos);
         push_flag!(flags, param_length, 10, self.addr_id);
@@ -314,6 +453,12 @@ impl MultipathTraceReply {
     }
 }
 
+impl WartsSized for TracelbType {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
 impl WartsSized for MultipathTraceNode {
     fn warts_size(&self) -> usize {
         self.flags.warts_size()
@@ -330,3 +475,91 @@ impl WartsSized for MultipathTraceLink {
             + self.param_length.unwrap() as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Object, TracelbNodeFlags, TracelbReplyFlags, TracelbType};
+
+    #[test]
+    fn type_and_flags() {
+        let data = include_bytes!("../data/tracelb_google_dns_v4_default.warts");
+        let trace = Object::all_from_bytes(data)
+            .into_iter()
+            .find_map(|object| match object {
+                Object::MultipathTraceroute(t) => Some(t),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(trace.type_, Some(TracelbType::UDPDport));
+        for node in &trace.nodes {
+            assert_eq!(
+                node.quoted_ttl.is_some(),
+                node.node_flags
+                    .is_some_and(|flags| flags.contains(TracelbNodeFlags::QTTL))
+            );
+        }
+        let replies: Vec<_> = trace
+            .links
+            .iter()
+            .flat_map(|link| &link.probe_sets)
+            .flat_map(|set| &set.probes)
+            .flat_map(|probe| &probe.replies)
+            .collect();
+        assert!(!replies.is_empty());
+        for reply in replies {
+            assert!(!reply.is_tcp());
+            assert!(reply
+                .reply_flags
+                .unwrap()
+                .contains(TracelbReplyFlags::REPLY_TTL));
+        }
+    }
+
+    #[test]
+    fn dereference_and_classify_replies() {
+        for (data, ipv6) in [
+            (
+                &include_bytes!("../data/tracelb_google_dns_v4_default.warts")[..],
+                false,
+            ),
+            (
+                &include_bytes!("../data/tracelb_google_dns_v6_default.warts")[..],
+                true,
+            ),
+        ] {
+            let mut traces = 0;
+            for mut object in Object::all_from_bytes(data) {
+                object.dereference();
+                let Object::MultipathTraceroute(trace) = object else {
+                    continue;
+                };
+                traces += 1;
+                let addresses = trace
+                    .nodes
+                    .iter()
+                    .map(|node| node.addr)
+                    .chain([trace.src_addr, trace.dst_addr]);
+                for address in addresses {
+                    assert_eq!(
+                        address.and_then(|a| a.ip()).map(|ip| ip.is_ipv6()),
+                        Some(ipv6)
+                    );
+                }
+                let replies: Vec<_> = trace
+                    .links
+                    .iter()
+                    .flat_map(|link| &link.probe_sets)
+                    .flat_map(|set| &set.probes)
+                    .flat_map(|probe| &probe.replies)
+                    .collect();
+                assert!(!replies.is_empty());
+                for reply in replies {
+                    let ip = reply.addr.and_then(|addr| addr.ip()).unwrap();
+                    assert_eq!(ip.is_ipv6(), ipv6);
+                    assert!(reply.icmp_kind().is_some());
+                }
+            }
+            assert_eq!(traces, 1);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use crate::object::AddressSlot;
+use crate::{Address, Flags, Timeval, WartsSized};
+use deku::prelude::*;
+use std::time::Duration;
+
+/// Reason why a UDP probe measurement stopped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian", type = "u8")]
+pub enum UdpProbeStopReason {
+    #[deku(id = "0x00")]
+    None,
+    /// Enough replies were received.
+    #[deku(id = "0x01")]
+    Done,
+    #[deku(id = "0x02")]
+    Halted,
+    #[deku(id = "0x03")]
+    Error,
+    #[deku(id_pat = "_")]
+    Unknown(u8),
+}
+
+/// An application-level UDP probe measurement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct UdpProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub length: u32,
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    /// Parameter length, included if any flags are set.
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// ID of the list of the UDP probe measurement, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub list_id: Option<u32>,
+    /// ID of the cycle of the UDP probe measurement, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub cycle_id: Option<u32>,
+    /// User-supplied identifier of the UDP probe measurement, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    pub user_id: Option<u32>,
+    /// Local address the probes are sent from, included if flag 4 is set.
+    #[deku(cond = "flags.get(4)")]
+    pub src_addr: Option<Address>,
+    /// Address of the application server the probes are sent to, included if flag 5 is set.
+    #[deku(cond = "flags.get(5)")]
+    pub dst_addr: Option<Address>,
+    /// Source port, included if flag 6 is set.
+    #[deku(cond = "flags.get(6)")]
+    pub src_port: Option<u16>,
+    /// Destination port, included if flag 7 is set.
+    #[deku(cond = "flags.get(7)")]
+    pub dst_port: Option<u16>,
+    /// Time measurement commenced, included if flag 8 is set.
+    #[deku(cond = "flags.get(8)")]
+    pub start_time: Option<Timeval>,
+    /// Time to wait for replies after the last probe, included if flag 9 is set.
+    #[deku(cond = "flags.get(9)")]
+    pub wait_timeout: Option<Timeval>,
+    /// Measurement flags, included if flag 10 is set.
+    #[deku(cond = "flags.get(10)")]
+    pub udpprobe_flags: Option<u8>,
+    /// Reason why the measurement stopped, included if flag 11 is set.
+    #[deku(cond = "flags.get(11)")]
+    pub stop_reason: Option<UdpProbeStopReason>,
+    /// Length of the payload, included if flag 12 is set.
+    #[deku(cond = "flags.get(12)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub payload_length: Option<u16>,
+    /// Payload of the probes, included if flag 13 is set.
+    #[deku(cond = "flags.get(13)", count = "payload_length.unwrap_or(0)")]
+    pub payload: Option<Vec<u8>>,
+    /// Number of probes to send, included if flag 14 is set.
+    #[deku(cond = "flags.get(14)")]
+    pub probe_count: Option<u8>,
+    /// Number of replies after which to stop, included if flag 15 is set.
+    #[deku(cond = "flags.get(15)")]
+    pub stop_count: Option<u8>,
+    /// Number of probes sent, included if flag 16 is set.
+    #[deku(cond = "flags.get(16)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub probe_sent: Option<u8>,
+    /// Probes sent.
+    #[deku(count = "probe_sent.unwrap_or(0)")]
+    pub probes: Vec<UdpProbeProbe>,
+}
+
+/// A probe sent during a UDP probe measurement, and its replies.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct UdpProbeProbe {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Transmit time, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub tx: Option<Timeval>,
+    /// Source port of the probe, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    pub src_port: Option<u16>,
+    /// Number of replies, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub reply_count: Option<u8>,
+    /// Replies received, if any.
+    #[deku(count = "reply_count.unwrap_or(0)")]
+    pub replies: Vec<UdpProbeReply>,
+}
+
+/// A reply to a UDP probe.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DekuRead, DekuWrite)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[deku(ctx = "endian: deku::ctx::Endian", endian = "endian")]
+pub struct UdpProbeReply {
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub flags: Flags,
+    #[deku(cond = "flags.any()")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub param_length: Option<u16>,
+    /// Receive time, included if flag 1 is set.
+    #[deku(cond = "flags.get(1)")]
+    pub rx: Option<Timeval>,
+    /// Length of the payload, included if flag 2 is set.
+    #[deku(cond = "flags.get(2)")]
+    #[cfg_attr(feature = "serde-skip-wire", serde(skip))]
+    pub payload_length: Option<u16>,
+    /// Payload of the reply, included if flag 3 is set.
+    #[deku(cond = "flags.get(3)", count = "payload_length.unwrap_or(0)")]
+    pub payload: Option<Vec<u8>>,
+}
+
+impl UdpProbe {
+    /// Local and server addresses; replies are matched by port and carry no address.
+    pub(crate) fn address_slots(&mut self) -> Vec<AddressSlot<'_>> {
+        vec![
+            AddressSlot::Optional(&mut self.src_addr, None),
+            AddressSlot::Optional(&mut self.dst_addr, None),
+        ]
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.payload_length = self.payload.as_ref().map(|payload| payload.len() as u16);
+        self.probe_sent = Some(self.probes.len() as u8);
+        push_flag!(flags, param_length, 1, self.list_id);
+        push_flag!(flags, param_length, 2, self.cycle_id);
+        push_flag!(flags, param_length, 3, self.user_id);
+        push_flag!(flags, param_length, 4, self.src_addr);
+        push_flag!(flags, param_length, 5, self.dst_addr);
+        push_flag!(flags, param_length, 6, self.src_port);
+        push_flag!(flags, param_length, 7, self.dst_port);
+        push_flag!(flags, param_length, 8, self.start_time);
+        push_flag!(flags, param_length, 9, self.wait_timeout);
+        push_flag!(flags, param_length, 10, self.udpprobe_flags);
+        push_flag!(flags, param_length, 11, self.stop_reason);
+        push_flag!(flags, param_length, 12, self.payload_length);
+        push_flag!(flags, param_length, 13, self.payload);
+        push_flag!(flags, param_length, 14, self.probe_count);
+        push_flag!(flags, param_length, 15, self.stop_count);
+        push_flag!(flags, param_length, 16, self.probe_sent);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self.length = (self.flags.warts_size()
+            + self.param_length.warts_size()
+            + param_length
+            + self.probes.warts_size()) as u32;
+        self
+    }
+}
+
+impl_measurement!(UdpProbe);
+
+impl UdpProbeProbe {
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.reply_count = Some(self.replies.len() as u8);
+        push_flag!(flags, param_length, 1, self.tx);
+        push_flag!(flags, param_length, 2, self.src_port);
+        push_flag!(flags, param_length, 3, self.reply_count);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl UdpProbeReply {
+    /// Round-trip time of the reply to the given probe.
+    pub fn rtt(&self, probe: &UdpProbeProbe) -> Option<Duration> {
+        self.rx?.checked_duration_since(probe.tx?)
+    }
+
+    pub fn finalize(mut self) -> Self {
+        let mut flags = Vec::new();
+        let mut param_length = 0;
+        self.payload_length = self.payload.as_ref().map(|payload| payload.len() as u16);
+        push_flag!(flags, param_length, 1, self.rx);
+        push_flag!(flags, param_length, 2, self.payload_length);
+        push_flag!(flags, param_length, 3, self.payload);
+        self.flags = Flags::from(flags);
+        self.param_length = Some(param_length as u16);
+        self
+    }
+}
+
+impl WartsSized for UdpProbeStopReason {
+    fn warts_size(&self) -> usize {
+        1
+    }
+}
+
+impl WartsSized for UdpProbeProbe {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+            + self.replies.warts_size()
+    }
+}
+
+impl WartsSized for UdpProbeReply {
+    fn warts_size(&self) -> usize {
+        self.flags.warts_size()
+            + self.param_length.warts_size()
+            + self.param_length.unwrap() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Address, Object, Timeval, UdpProbe, UdpProbeProbe, UdpProbeReply, UdpProbeStopReason,
+    };
+    use deku::bitvec::BitView;
+    use deku::prelude::*;
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    #[test]
+    fn round_trip() {
+        let reply = UdpProbeReply {
+            flags: Default::default(),
+            param_length: None,
+            rx: Some(Timeval::new(1644339038, 25_000)),
+            payload_length: None,
+            payload: Some(vec![0x24, 0x01, 0x00, 0xe9]),
+        }
+        .finalize();
+        let probe = UdpProbeProbe {
+            flags: Default::default(),
+            param_length: None,
+            tx: Some(Timeval::new(1644339038, 5_000)),
+            src_port: Some(40000),
+            reply_count: None,
+            replies: vec![reply],
+        }
+        .finalize();
+        assert_eq!(
+            probe.replies[0].rtt(&probe),
+            Some(Duration::from_millis(20))
+        );
+        let udpprobe = UdpProbe {
+            length: 0,
+            flags: Default::default(),
+            param_length: None,
+            list_id: Some(1),
+            cycle_id: Some(1),
+            user_id: None,
+            src_addr: Some(Address::from(Ipv4Addr::new(192, 0, 2, 1))),
+            dst_addr: Some(Address::from(Ipv4Addr::new(198, 51, 100, 123))),
+            src_port: Some(40000),
+            dst_port: Some(123),
+            start_time: Some(Timeval::new(1644339038, 0)),
+            wait_timeout: Some(Timeval::new(1, 0)),
+            udpprobe_flags: None,
+            stop_reason: Some(UdpProbeStopReason::Done),
+            payload_length: None,
+            payload: Some(vec![0x23, 0x00, 0x00, 0x00]),
+            probe_count: Some(1),
+            stop_count: Some(1),
+            probe_sent: None,
+            probes: vec![probe],
+        }
+        .finalize();
+        let bytes = Object::UdpProbe(udpprobe.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 8 + udpprobe.length as usize);
+        let objects = Object::all_from_bytes(&bytes);
+        assert_eq!(objects, vec![Object::UdpProbe(udpprobe)]);
+    }
+
+    #[test]
+    fn reply_payload_parameter() {
+        // Flags 1 (time), 2 (length) and 3 (payload), 12 bytes of parameters.
+        let input = [
+            0x07, 0x00, 0x0c, 0x62, 0x02, 0x46, 0x5e, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x24,
+            0x01,
+        ];
+        let (rest, reply) = UdpProbeReply::read(input.view_bits(), deku::ctx::Endian::Big).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(reply.rx, Some(Timeval::new(1644316254, 0)));
+        assert_eq!(reply.payload, Some(vec![0x24, 0x01]));
+        assert_eq!(reply.clone().finalize(), reply);
+    }
+}